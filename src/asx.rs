@@ -0,0 +1,292 @@
+//! Reading and writing the Windows Media **ASX** playlist format.
+//!
+//! An **ASX** file looks like this:
+//!
+//! ```xml
+//! <asx version="3.0">
+//!   <title>Example Playlist</title>
+//!   <entry>
+//!     <title>Example title</title>
+//!     <author>Example Artist</author>
+//!     <ref href="http://www.example.com/~user/Mine.mp3" />
+//!     <duration value="00:05:21.000" />
+//!   </entry>
+//! </asx>
+//! ```
+//!
+//! Each `<entry>` maps to an `EntryExt`:
+//!
+//! - The first `<ref href>` maps to the `Entry`. An `<entryref href>` in place of an `<entry>` is
+//!   read as an entry with only a location.
+//! - `<title>` maps to the `ExtInf` name.
+//! - `<duration value>` maps to the `ExtInf` duration. A missing duration is read as an `Unknown`
+//!   duration, while `Live` and `Unknown` durations are written without one.
+//! - `<author>` maps to the `ExtInf` attribute named by `CREATOR_ATTRIBUTE`.
+//!
+//! Element and attribute names are matched case-insensitively.
+
+use {Duration, Entry, EntryExt, ExtInf, CREATOR_ATTRIBUTE};
+use markup::{self, Element};
+use read::read_entry;
+use std;
+use std::io::{Read, Write};
+use xml;
+
+/// A reader that reads the **ASX** format.
+///
+/// The whole document is read upon construction.
+pub struct Reader {
+    /// The `<title>` of the playlist, if any.
+    title: Option<String>,
+    /// The entries that have not yet been yielded.
+    entries: std::vec::IntoIter<EntryExt>,
+}
+
+/// A writer that writes the **ASX** format.
+///
+/// The opening `<asx>` tag is written upon construction and the closing tag is written by
+/// `finish`. If `finish` is not called, the closing tag is written when the writer is dropped,
+/// though any errors that occur in the process cannot be handled.
+pub struct Writer<W>
+    where W: Write,
+{
+    /// The writer to which the **ASX** format is written.
+    writer: W,
+    /// Whether or not the closing tag has been written.
+    finished: bool,
+}
+
+/// An iterator that yields the `Entry`s of an **ASX** playlist.
+pub struct Entries<'r> {
+    reader: &'r mut Reader,
+}
+
+/// An iterator that yields the `EntryExt`s of an **ASX** playlist.
+pub struct EntryExts<'r> {
+    reader: &'r mut Reader,
+}
+
+/// Errors that may occur when reading the **ASX** format.
+#[derive(Debug)]
+pub enum Error {
+    /// The document was not well-formed XML.
+    Xml(xml::reader::Error),
+    /// The root element of the document was not an `<asx>`.
+    AsxNotFound,
+    /// The `value` of a `<duration>` element was not a valid clock value.
+    InvalidDuration(String),
+    /// Errors produced when opening a file.
+    Io(std::io::Error),
+}
+
+
+impl Reader {
+
+    /// Read an **ASX** playlist from the given reader.
+    pub fn new<R>(reader: R) -> Result<Self, Error>
+        where R: Read,
+    {
+        let root = match markup::parse(reader)? {
+            Some(root) if root.local_name().eq_ignore_ascii_case("asx") => root,
+            _ => return Err(Error::AsxNotFound),
+        };
+
+        let title = root.element("title").map(Element::text);
+        let mut entries = Vec::new();
+        for element in root.elements() {
+            let name = element.local_name();
+            if name.eq_ignore_ascii_case("entry") {
+                if let Some(entry_ext) = read_entry_element(element)? {
+                    entries.push(entry_ext);
+                }
+            } else if name.eq_ignore_ascii_case("entryref") {
+                if let Some(href) = element.attribute("href") {
+                    entries.push(read_entry(href.trim()).extend(Duration::Unknown, ""));
+                }
+            }
+        }
+
+        Ok(Reader { title, entries: entries.into_iter() })
+    }
+
+    /// Attempts to read an **ASX** playlist from the specified file.
+    ///
+    /// This is a convenience constructor that opens a `File`, wraps it in a `BufReader` and then
+    /// constructs a `Reader` from it.
+    pub fn open<P>(filename: P) -> Result<Self, Error>
+        where P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(filename)?;
+        let buf_reader = std::io::BufReader::new(file);
+        Self::new(buf_reader)
+    }
+
+    /// The `<title>` of the playlist, if one was present.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_ref().map(|s| &s[..])
+    }
+
+    /// Produce an iterator that yields `Entry`s, discarding their titles and durations.
+    pub fn entries(&mut self) -> Entries<'_> {
+        Entries { reader: self }
+    }
+
+    /// Produce an iterator that yields `EntryExt`s.
+    pub fn entry_exts(&mut self) -> EntryExts<'_> {
+        EntryExts { reader: self }
+    }
+
+}
+
+impl<W> Writer<W>
+    where W: Write,
+{
+
+    /// Create a writer that writes an **ASX** playlist without a title.
+    pub fn new(writer: W) -> Result<Self, std::io::Error> {
+        Self::with_title(writer, None)
+    }
+
+    /// Create a writer that writes an **ASX** playlist with the given title.
+    ///
+    /// The opening `<asx>` tag and the `<title>` are written immediately.
+    pub fn with_title(mut writer: W, title: Option<&str>) -> Result<Self, std::io::Error> {
+        writeln!(writer, "<asx version=\"3.0\">")?;
+        if let Some(title) = title {
+            markup::write_element(&mut writer, &Element::with_text("title", title), 1)?;
+        }
+        Ok(Writer { writer, finished: false })
+    }
+
+    /// Attempt to write the given `Entry` as an `<entry>` with only a `<ref>`.
+    pub fn write_entry(&mut self, entry: &Entry) -> Result<(), std::io::Error> {
+        let mut element = Element::new("entry");
+        element.children.push(markup::Node::Element(ref_element(entry)));
+        markup::write_element(&mut self.writer, &element, 1)
+    }
+
+    /// Attempt to write the given `EntryExt` as an `<entry>`.
+    pub fn write_entry_ext(&mut self, entry_ext: &EntryExt) -> Result<(), std::io::Error> {
+        let extinf = &entry_ext.extinf;
+        let mut element = Element::new("entry");
+        if !extinf.name.is_empty() {
+            element.push_text_element("title", &extinf.name[..]);
+        }
+        if let Some(author) = extinf.attribute(CREATOR_ATTRIBUTE) {
+            element.push_text_element("author", author);
+        }
+        element.children.push(markup::Node::Element(ref_element(&entry_ext.entry)));
+        if let Some(secs) = extinf.duration.as_secs_f64() {
+            let mut duration = Element::new("duration");
+            let value = markup::format_clock(secs);
+            duration.attributes.push(("value".to_string(), value));
+            element.children.push(markup::Node::Element(duration));
+        }
+        markup::write_element(&mut self.writer, &element, 1)
+    }
+
+    /// Write the closing `</asx>` tag and flush the inner writer.
+    pub fn finish(mut self) -> Result<(), std::io::Error> {
+        self.finished = true;
+        writeln!(self.writer, "</asx>")?;
+        self.writer.flush()
+    }
+
+}
+
+
+/// Read an `EntryExt` from the given `<entry>` element.
+///
+/// Returns `None` if the entry has no `<ref href>`.
+fn read_entry_element(element: &Element) -> Result<Option<EntryExt>, Error> {
+    let entry = match element.element("ref").and_then(|r| r.attribute("href")) {
+        Some(href) => read_entry(href.trim()),
+        None => return Ok(None),
+    };
+    let name = element.element("title").map(Element::text).unwrap_or_default();
+    let duration = match element.element("duration").and_then(|d| d.attribute("value")) {
+        None => Duration::Unknown,
+        Some(value) => match markup::parse_clock(value) {
+            Some(secs) => Duration::from_secs_f64(secs),
+            None => return Err(Error::InvalidDuration(value.to_string())),
+        },
+    };
+    let mut extinf = ExtInf::new(duration, name);
+    if let Some(author) = element.element("author") {
+        extinf.set_attribute(CREATOR_ATTRIBUTE, author.text());
+    }
+    Ok(Some(EntryExt { entry, extinf }))
+}
+
+/// Produce the `<ref href>` element for the given `Entry`.
+fn ref_element(entry: &Entry) -> Element {
+    let href = match *entry {
+        Entry::Path(ref path) => path.display().to_string(),
+        Entry::Url(ref url) | Entry::Uri(ref url) => url.to_string(),
+    };
+    let mut element = Element::new("ref");
+    element.attributes.push(("href".to_string(), href));
+    element
+}
+
+
+impl<'r> Iterator for Entries<'r> {
+    type Item = Entry;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.entries.next().map(|entry_ext| entry_ext.entry)
+    }
+}
+
+impl<'r> Iterator for EntryExts<'r> {
+    type Item = EntryExt;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.entries.next()
+    }
+}
+
+
+impl<W> Drop for Writer<W>
+    where W: Write,
+{
+    fn drop(&mut self) {
+        if !self.finished {
+            writeln!(self.writer, "</asx>").ok();
+            self.writer.flush().ok();
+        }
+    }
+}
+
+
+impl From<xml::reader::Error> for Error {
+    fn from(err: xml::reader::Error) -> Self {
+        Error::Xml(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Xml(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            Error::AsxNotFound | Error::InvalidDuration(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::Xml(ref err) => err.fmt(f),
+            Error::AsxNotFound => write!(f, "the root `<asx>` element was not found"),
+            Error::InvalidDuration(ref value) =>
+                write!(f, "`{}` is not a valid duration", value),
+            Error::Io(ref err) => err.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! The `m3u` command-line tool for validating, converting and inspecting playlists.
+//!
+//! Built with `cargo build --features cli`. Run `m3u help` for usage.
+
+extern crate m3u;
+
+use m3u::{Format, PathProblemKind, Playlist, ReadEntryExtError};
+use std::io::{Read, Write};
+
+const USAGE: &str = "\
+Usage: m3u <command> [options] [FILE...]
+
+Reads each FILE, or stdin if no FILE or `-` is given, and writes to stdout. The format of each
+input is detected from its content.
+
+Commands:
+    validate            Parse each playlist and report any problems
+    convert --to FMT    Convert a playlist to the given format
+    stats               Print the entry count, total duration and path vs URL breakdown
+    fmt                 Rewrite a playlist in its own format in canonical form
+    cat [--to FMT]      Concatenate playlists, by default in the format of the first
+    remap --rule FROM=TO [--case-sensitive]
+                        Replace the path prefix FROM with TO within each FILE in place. The
+                        first matching of any number of rules is applied
+    generate [--single] [--extended] [--extensions EXT,...] DIR...
+                        Write a playlist of the media files within each directory of each DIR
+                        tree, or a single playlist at the root of each tree with `--single`,
+                        and print the paths of the written playlists
+    help                Print this message
+
+Formats: m3u, m3u-ext, pls, xspf, asx, wpl, cue
+";
+
+/// Errors that abort the command.
+type Error = Box<dyn std::error::Error>;
+
+/// A playlist read from a named input.
+struct Input {
+    name: String,
+    bytes: Vec<u8>,
+}
+
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let code = match run(&args) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("m3u: {}", err);
+            2
+        },
+    };
+    std::process::exit(code);
+}
+
+/// Run the command described by the given arguments, returning the exit code.
+fn run(args: &[String]) -> Result<i32, Error> {
+    let (command, args) = match args.split_first() {
+        Some((command, args)) => (&command[..], args),
+        None => {
+            eprint!("{}", USAGE);
+            return Ok(2);
+        },
+    };
+
+    // Split the remaining arguments into the options and the inputs.
+    let mut to = None;
+    let mut remapper = m3u::Remapper::new();
+    let mut generator = m3u::Generator::new();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match &arg[..] {
+            "-t" | "--to" => {
+                let name = args.next().ok_or("`--to` requires a format")?;
+                let format = parse_format(name);
+                to = Some(format.ok_or_else(|| format!("unknown format `{}`", name))?);
+            },
+            "--rule" => {
+                let rule = args.next().ok_or("`--rule` requires `FROM=TO`")?;
+                let eq = rule.find('=').ok_or("`--rule` requires `FROM=TO`")?;
+                remapper = remapper.rule(&rule[..eq], &rule[eq + 1..]);
+            },
+            "--case-sensitive" => remapper = remapper.case_sensitive(true),
+            "--single" => generator = generator.grouping(m3u::Grouping::Single),
+            "--extended" => generator = generator.extended(true),
+            "--extensions" => {
+                let extensions = args.next().ok_or("`--extensions` requires `EXT,...`")?;
+                let expander = m3u::Expander::new().extensions(extensions.split(','));
+                generator = generator.expander(expander);
+            },
+            "-h" | "--help" => {
+                print!("{}", USAGE);
+                return Ok(0);
+            },
+            arg if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option `{}`", arg).into());
+            },
+            _ => files.push(&arg[..]),
+        }
+    }
+    if files.is_empty() {
+        files.push("-");
+    }
+
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    match command {
+        "validate" => validate(&files, &mut out),
+        "convert" => {
+            let to = to.ok_or("`convert` requires `--to <format>`")?;
+            let input = single_input(&files)?;
+            write_playlist(to, &read_playlist(&input)?, &mut out)?;
+            Ok(0)
+        },
+        "stats" => stats(&files, &mut out),
+        "fmt" => {
+            let input = single_input(&files)?;
+            let playlist = read_playlist(&input)?;
+            write_playlist(playlist.format, &playlist, &mut out)?;
+            Ok(0)
+        },
+        "cat" => {
+            let mut playlists = Vec::new();
+            for file in &files {
+                playlists.push(read_playlist(&read_input(file)?)?);
+            }
+            let mut concatenated = playlists.remove(0);
+            for playlist in playlists {
+                concatenated.entries.extend(playlist.entries);
+            }
+            let format = to.unwrap_or(concatenated.format);
+            write_playlist(format, &concatenated, &mut out)?;
+            Ok(0)
+        },
+        "remap" => remap(&remapper, &files, &mut out),
+        "generate" => {
+            if files.contains(&"-") {
+                return Err("`generate` requires a directory".into());
+            }
+            for dir in &files {
+                for path in generator.generate_files(dir)? {
+                    writeln!(out, "{}", path.display())?;
+                }
+            }
+            Ok(0)
+        },
+        "help" => {
+            print!("{}", USAGE);
+            Ok(0)
+        },
+        command => Err(format!("unknown command `{}`, see `m3u help`", command).into()),
+    }
+}
+
+/// Parse each input and report diagnostics, returning `1` if any input could not be read.
+fn validate<W>(files: &[&str], out: &mut W) -> Result<i32, Error>
+    where W: Write,
+{
+    let mut code = 0;
+    for file in files {
+        let input = read_input(file)?;
+        let playlist = match read_playlist(&input) {
+            Ok(playlist) => playlist,
+            Err(err) => {
+                writeln!(out, "{}: error: {}", input.name, err)?;
+                code = 1;
+                continue;
+            },
+        };
+
+        // Extended M3U entries should each be preceded by an `#EXTINF:` tag.
+        if playlist.format == Format::M3uExt || playlist.format == Format::HlsMedia {
+            let mut reader = match m3u::Reader::new_ext(&input.bytes[..]) {
+                Ok(reader) => reader,
+                Err(err) => {
+                    writeln!(out, "{}: error: {}", input.name, err)?;
+                    code = 1;
+                    continue;
+                },
+            };
+            for (i, entry_ext) in reader.entry_exts().enumerate() {
+                if let Err(ReadEntryExtError::ExtInfNotFound(_)) = entry_ext {
+                    writeln!(out, "{}: warning: entry {} has no valid #EXTINF tag",
+                             input.name, i + 1)?;
+                }
+            }
+        }
+
+        // Check that the paths of file inputs point to accessible files.
+        if *file != "-" {
+            let entries = playlist.entries.iter().map(|e| &e.entry);
+            for problem in m3u::validate_paths(file, entries).problems {
+                let problem_name = match problem.kind {
+                    PathProblemKind::Missing => "is missing".to_string(),
+                    PathProblemKind::Directory => "is a directory".to_string(),
+                    PathProblemKind::PermissionDenied => "is not accessible".to_string(),
+                    PathProblemKind::Io(kind) => format!("could not be accessed ({:?})", kind),
+                };
+                writeln!(out, "{}: warning: entry {} `{}` {}",
+                         input.name, problem.index + 1, problem.path.display(), problem_name)?;
+            }
+        }
+
+        for (i, entry_ext) in playlist.entries.iter().enumerate() {
+            let first = playlist.entries.iter().position(|e| e.entry == entry_ext.entry);
+            if let Some(first) = first.filter(|&first| first < i) {
+                writeln!(out, "{}: warning: entry {} duplicates entry {}",
+                         input.name, i + 1, first + 1)?;
+            }
+        }
+
+        writeln!(out, "{}: ok ({}, {} entries)",
+                 input.name, format_name(playlist.format), playlist.entries.len())?;
+    }
+    Ok(code)
+}
+
+/// Print the statistics of each input.
+fn stats<W>(files: &[&str], out: &mut W) -> Result<i32, Error>
+    where W: Write,
+{
+    for file in files {
+        let input = read_input(file)?;
+        let playlist = read_playlist(&input)?;
+        let entries = &playlist.entries;
+        let paths = entries.iter().filter(|e| e.entry.is_path()).count();
+        let known: Vec<f64> = entries.iter()
+            .filter_map(|e| e.extinf.duration.as_secs_f64())
+            .collect();
+        let total_secs = known.iter().sum::<f64>().round() as u64;
+
+        if files.len() > 1 {
+            writeln!(out, "{}:", input.name)?;
+        }
+        writeln!(out, "format: {}", format_name(playlist.format))?;
+        writeln!(out, "entries: {}", entries.len())?;
+        writeln!(out, "paths: {}", paths)?;
+        writeln!(out, "urls: {}", entries.len() - paths)?;
+        write!(out, "total duration: {}:{:02}:{:02}",
+               total_secs / 3_600, total_secs / 60 % 60, total_secs % 60)?;
+        match entries.len() - known.len() {
+            0 => writeln!(out)?,
+            unknown => writeln!(out, " ({} entries of unknown duration)", unknown)?,
+        }
+    }
+    Ok(0)
+}
+
+/// Remap the paths within each file in place and report the entries that matched no rule.
+fn remap<W>(remapper: &m3u::Remapper, files: &[&str], out: &mut W) -> Result<i32, Error>
+    where W: Write,
+{
+    if files.contains(&"-") {
+        return Err("`remap` rewrites files in place and cannot read stdin".into());
+    }
+    let mut code = 0;
+    for (path, result) in remapper.remap_files(files) {
+        match result {
+            Ok(report) => {
+                writeln!(out, "{}: {} remapped, {} unmatched",
+                         path.display(), report.remapped, report.unmatched.len())?;
+                for index in report.unmatched {
+                    writeln!(out, "{}: warning: entry {} matched no rule",
+                             path.display(), index + 1)?;
+                }
+            },
+            Err(err) => {
+                writeln!(out, "{}: error: {}", path.display(), err)?;
+                code = 1;
+            },
+        }
+    }
+    Ok(code)
+}
+
+/// Write the given playlist in the given format.
+fn write_playlist<W>(format: Format, playlist: &Playlist, out: &mut W) -> Result<(), Error>
+    where W: Write,
+{
+    if format == Format::HlsMedia || format == Format::HlsMaster {
+        return Err("HLS playlists cannot be written, convert to `m3u-ext` instead".into());
+    }
+    playlist.write(format, out)?;
+    Ok(())
+}
+
+/// Read the given file, or stdin if the file is `-`.
+fn read_input(file: &str) -> Result<Input, Error> {
+    let mut bytes = Vec::new();
+    if file == "-" {
+        std::io::stdin().read_to_end(&mut bytes)?;
+        return Ok(Input { name: "<stdin>".to_string(), bytes });
+    }
+    std::fs::File::open(file)
+        .and_then(|mut f| f.read_to_end(&mut bytes))
+        .map_err(|err| format!("{}: {}", file, err))?;
+    Ok(Input { name: file.to_string(), bytes })
+}
+
+/// Read the only input, failing if more than one was given.
+fn single_input(files: &[&str]) -> Result<Input, Error> {
+    match *files {
+        [file] => read_input(file),
+        _ => Err("expected a single input".into()),
+    }
+}
+
+/// Read the playlist from the given input.
+fn read_playlist(input: &Input) -> Result<Playlist, Error> {
+    m3u::read_any(&input.bytes[..]).map_err(|err| format!("{}: {}", input.name, err).into())
+}
+
+/// The format named on the command line.
+fn parse_format(name: &str) -> Option<Format> {
+    let format = match &name.to_ascii_lowercase()[..] {
+        "m3u" => Format::M3u,
+        "m3u-ext" | "extm3u" | "m3u8" => Format::M3uExt,
+        "pls" => Format::Pls,
+        "xspf" => Format::Xspf,
+        "asx" => Format::Asx,
+        "wpl" => Format::Wpl,
+        "cue" => Format::Cue,
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// The name of the given format as printed by the tool.
+fn format_name(format: Format) -> &'static str {
+    match format {
+        Format::M3u => "m3u",
+        Format::M3uExt => "m3u-ext",
+        Format::HlsMedia => "hls-media",
+        Format::HlsMaster => "hls-master",
+        Format::Pls => "pls",
+        Format::Xspf => "xspf",
+        Format::Asx => "asx",
+        Format::Wpl => "wpl",
+        Format::Cue => "cue",
+    }
+}
@@ -0,0 +1,245 @@
+//! Streaming conversion between `EntryExt`s and CSV.
+//!
+//! Each record describes a single entry using the following columns:
+//!
+//! - `location`: the path or URL of the entry.
+//! - `kind`: one of `path`, `url` or `uri`.
+//! - `duration`: the duration of the media in seconds, `-1` for a live stream or empty if unknown.
+//! - `name`: the name of the media.
+//!
+//! These are followed by one column per `ExtInf` attribute key. An empty attribute cell indicates
+//! that the entry does not have that attribute.
+
+use {Duration, Entry, EntryExt, ExtInf};
+use csv_crate;
+use std;
+use std::io::{Read, Write};
+use url;
+
+/// The columns that precede the attribute columns.
+const COLUMNS: [&str; 4] = ["location", "kind", "duration", "name"];
+
+/// A reader that lazily reads `EntryExt`s from CSV records.
+///
+/// The header record is used to locate the columns, which may appear in any order. Any columns
+/// other than `location`, `kind`, `duration` and `name` are read as attributes.
+pub struct Reader<R>
+    where R: Read,
+{
+    /// The underlying CSV reader.
+    reader: csv_crate::Reader<R>,
+    /// The index of each of the `COLUMNS` within a record.
+    columns: [usize; 4],
+    /// The index and key of each attribute column.
+    attributes: Vec<(usize, String)>,
+    /// Buffer used for reading records.
+    record: csv_crate::StringRecord,
+}
+
+/// A writer that writes `EntryExt`s as CSV records.
+///
+/// The header record is written upon construction, so the attribute keys to write must be known
+/// up front. Attributes of an entry whose keys were not given are not written.
+pub struct Writer<W>
+    where W: Write,
+{
+    /// The underlying CSV writer.
+    writer: csv_crate::Writer<W>,
+    /// The attribute key for each attribute column.
+    attributes: Vec<String>,
+}
+
+/// An iterator that yields the `EntryExt`s of CSV records.
+pub struct EntryExts<'r, R>
+    where R: 'r + Read,
+{
+    reader: &'r mut Reader<R>,
+}
+
+/// Errors that may occur while reading or writing CSV.
+#[derive(Debug)]
+pub enum Error {
+    /// Errors produced by the underlying CSV reader or writer.
+    Csv(csv_crate::Error),
+    /// One of the required columns was missing from the header record.
+    MissingColumn(&'static str),
+    /// The `kind` cell was not one of `path`, `url` or `uri`.
+    InvalidKind(String),
+    /// The `duration` cell could not be parsed as a number of seconds.
+    InvalidDuration(String),
+    /// The `location` of a `url` entry could not be parsed.
+    InvalidUrl(url::ParseError),
+}
+
+
+impl<R> Reader<R>
+    where R: Read,
+{
+
+    /// Create a reader that reads CSV records from the given reader.
+    ///
+    /// The header record is read immediately.
+    pub fn new(reader: R) -> Result<Self, Error> {
+        let mut reader = csv_crate::ReaderBuilder::new().flexible(true).from_reader(reader);
+        let headers = reader.headers()?.clone();
+
+        let mut columns = [0; 4];
+        for (column, name) in columns.iter_mut().zip(COLUMNS.iter()) {
+            *column = match headers.iter().position(|h| h == *name) {
+                Some(index) => index,
+                None => return Err(Error::MissingColumn(name)),
+            };
+        }
+        let attributes = headers.iter()
+            .enumerate()
+            .filter(|&(_, h)| !COLUMNS.contains(&h))
+            .map(|(i, h)| (i, h.to_string()))
+            .collect();
+
+        Ok(Reader {
+            reader,
+            columns,
+            attributes,
+            record: csv_crate::StringRecord::new(),
+        })
+    }
+
+    /// Attempt to read the next `EntryExt`.
+    ///
+    /// Returns `Ok(None)` when there are no more records.
+    fn read_next_entry(&mut self) -> Result<Option<EntryExt>, Error> {
+        if !self.reader.read_record(&mut self.record)? {
+            return Ok(None);
+        }
+        let record = &self.record;
+        let cell = |index: usize| record.get(index).unwrap_or("");
+        let [location, kind, duration, name] = self.columns;
+
+        let entry = match cell(kind) {
+            "path" => Entry::Path(cell(location).into()),
+            "url" => Entry::Url(url::Url::parse(cell(location)).map_err(Error::InvalidUrl)?),
+            "uri" => Entry::Uri(url::Url::parse(cell(location)).map_err(Error::InvalidUrl)?),
+            other => return Err(Error::InvalidKind(other.to_string())),
+        };
+        let duration = match cell(duration).trim() {
+            "" => Duration::Unknown,
+            secs => match secs.parse() {
+                Ok(duration) => duration,
+                Err(_) => return Err(Error::InvalidDuration(cell(duration).to_string())),
+            },
+        };
+        let mut extinf = ExtInf::new(duration, cell(name));
+        for (index, key) in &self.attributes {
+            let value = cell(*index);
+            if !value.is_empty() {
+                extinf.attributes.push((key.clone(), value.to_string()));
+            }
+        }
+
+        Ok(Some(EntryExt { entry, extinf }))
+    }
+
+    /// Produce an iterator that yields `EntryExt`s.
+    ///
+    /// All `EntryExt`s are lazily read from the inner reader.
+    pub fn entry_exts(&mut self) -> EntryExts<'_, R> {
+        EntryExts { reader: self }
+    }
+
+}
+
+impl<W> Writer<W>
+    where W: Write,
+{
+
+    /// Create a writer that writes CSV records without any attribute columns.
+    ///
+    /// The header record is written immediately.
+    pub fn new(writer: W) -> Result<Self, Error> {
+        Self::with_attributes(writer, Vec::<String>::new())
+    }
+
+    /// Create a writer that writes CSV records with a column for each of the given attribute keys.
+    ///
+    /// The header record is written immediately.
+    pub fn with_attributes<I>(writer: W, attributes: I) -> Result<Self, Error>
+        where I: IntoIterator,
+              I::Item: Into<String>,
+    {
+        let attributes: Vec<String> = attributes.into_iter().map(Into::into).collect();
+        let mut writer = csv_crate::Writer::from_writer(writer);
+        writer.write_record(COLUMNS.iter().cloned().chain(attributes.iter().map(|k| &k[..])))?;
+        Ok(Writer { writer, attributes })
+    }
+
+    /// Attempt to write the given `EntryExt` as a single record.
+    pub fn write_entry(&mut self, entry_ext: &EntryExt) -> Result<(), Error> {
+        let (location, kind) = match entry_ext.entry {
+            Entry::Path(ref path) => (path.display().to_string(), "path"),
+            Entry::Url(ref url) => (url.to_string(), "url"),
+            Entry::Uri(ref uri) => (uri.to_string(), "uri"),
+        };
+        let extinf = &entry_ext.extinf;
+        let duration = match extinf.duration {
+            Duration::Unknown => String::new(),
+            duration => duration.to_string(),
+        };
+        let attributes = self.attributes.iter()
+            .map(|key| extinf.attribute(key).unwrap_or(""));
+        let fields = [&location[..], kind, &duration[..], &extinf.name[..]];
+        self.writer.write_record(fields.iter().cloned().chain(attributes))?;
+        Ok(())
+    }
+
+    /// `Flush` the `writer` output stream, ensuring that all intermediately buffered entries reach
+    /// their destination.
+    pub fn flush(mut self) -> Result<(), std::io::Error> {
+        self.writer.flush()
+    }
+
+}
+
+
+impl<'r, R> Iterator for EntryExts<'r, R>
+    where R: Read,
+{
+    type Item = Result<EntryExt, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.read_next_entry() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+
+impl From<csv_crate::Error> for Error {
+    fn from(err: csv_crate::Error) -> Self {
+        Error::Csv(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Csv(ref err) => Some(err),
+            Error::InvalidUrl(ref err) => Some(err),
+            Error::MissingColumn(_) | Error::InvalidKind(_) | Error::InvalidDuration(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::Csv(ref err) => err.fmt(f),
+            Error::MissingColumn(column) => write!(f, "the `{}` column is missing", column),
+            Error::InvalidKind(ref kind) =>
+                write!(f, "expected a `kind` of `path`, `url` or `uri` but found `{}`", kind),
+            Error::InvalidDuration(ref duration) =>
+                write!(f, "`{}` is not a valid duration in seconds", duration),
+            Error::InvalidUrl(ref err) => err.fmt(f),
+        }
+    }
+}
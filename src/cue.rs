@@ -0,0 +1,544 @@
+//! Reading and writing **CUE** sheets, and converting them to and from `EntryExt`s.
+//!
+//! A **CUE** sheet describes the tracks within one or more files, usually a single image of an
+//! entire album:
+//!
+//! ```text
+//! PERFORMER "Example Artist"
+//! TITLE "Greatest Hits"
+//! FILE "Greatest Hits.flac" WAVE
+//!   TRACK 01 AUDIO
+//!     TITLE "Example title"
+//!     INDEX 01 00:00:00
+//!   TRACK 02 AUDIO
+//!     TITLE "Sample title"
+//!     INDEX 01 05:21:00
+//! ```
+//!
+//! As an M3U entry cannot address a part of a file, each track is converted to an `EntryExt`
+//! pointing to the whole file with `START_ATTRIBUTE` and `END_ATTRIBUTE` attributes holding the
+//! offsets of the track within the file in seconds. The performer of each track is stored in the
+//! attribute named by `CREATOR_ATTRIBUTE`.
+
+use {Duration, Entry, EntryExt, ExtInf, CREATOR_ATTRIBUTE};
+use std;
+use std::io::{BufRead, Write};
+
+/// The `ExtInf` attribute holding the offset in seconds at which a track starts within its file.
+pub const START_ATTRIBUTE: &str = "start";
+/// The `ExtInf` attribute holding the offset in seconds at which a track ends within its file.
+///
+/// This is omitted for the last track of each file.
+pub const END_ATTRIBUTE: &str = "end";
+
+/// The number of frames per second used by **CUE** sheet timestamps.
+pub const FRAMES_PER_SEC: u32 = 75;
+
+/// A parsed **CUE** sheet.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CueSheet {
+    /// `REM` comments preceding the first `FILE` as `(key, value)` pairs, e.g. `("DATE", "1999")`.
+    pub rem: Vec<(String, String)>,
+    /// The `PERFORMER` of the whole sheet.
+    pub performer: Option<String>,
+    /// The `TITLE` of the whole sheet.
+    pub title: Option<String>,
+    /// Any other commands preceding the first `FILE`, such as `CATALOG`, as unparsed lines.
+    pub other: Vec<String>,
+    /// The files described by the sheet.
+    pub files: Vec<File>,
+}
+
+/// A `FILE` within a **CUE** sheet and the tracks it contains.
+#[derive(Clone, Debug, PartialEq)]
+pub struct File {
+    /// The path to the file, usually relative to the **CUE** sheet.
+    pub path: String,
+    /// The type of the file, e.g. `WAVE` or `MP3`.
+    pub kind: String,
+    /// The tracks within the file.
+    pub tracks: Vec<Track>,
+}
+
+/// A `TRACK` within a `FILE`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track {
+    /// The track number.
+    pub number: u32,
+    /// The type of the track, e.g. `AUDIO`.
+    pub kind: String,
+    /// The `TITLE` of the track.
+    pub title: Option<String>,
+    /// The `PERFORMER` of the track.
+    pub performer: Option<String>,
+    /// `REM` comments within the track as `(key, value)` pairs.
+    pub rem: Vec<(String, String)>,
+    /// The `INDEX` points of the track.
+    pub indices: Vec<Index>,
+    /// The `INDEX` points of the track that lie within the following `FILE`, preceding that
+    /// file's first `TRACK`, as written by EAC's "gaps appended" sheets.
+    pub continued_indices: Vec<Index>,
+    /// Any other commands within the track, such as `ISRC` or `FLAGS`, as unparsed lines.
+    pub other: Vec<String>,
+}
+
+/// An `INDEX` point within a `Track`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Index {
+    /// The index number. `0` marks the pregap and `1` marks the start of the track.
+    pub number: u32,
+    /// The offset of the index within the file.
+    pub time: Time,
+}
+
+/// A `mm:ss:ff` offset within a file, where there are `FRAMES_PER_SEC` frames per second.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    /// The offset in frames.
+    pub frames: u64,
+}
+
+/// Errors that may occur when reading a **CUE** sheet.
+#[derive(Debug)]
+pub enum Error {
+    /// The line with the given number (starting from `1`) was malformed.
+    InvalidLine(usize),
+    /// A `TRACK` on the line with the given number appeared before any `FILE`.
+    TrackWithoutFile(usize),
+    /// A `TITLE`, `PERFORMER` or `INDEX` on the line with the given number appeared within the
+    /// first `FILE` but before any `TRACK`.
+    CommandWithoutTrack(usize),
+    /// Errors produced by the `BufRead::read_line` method.
+    BufRead(std::io::Error),
+}
+
+/// A `Time` that is not of the form `mm:ss:ff` or whose number of frames does not fit a `u64`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTimeError;
+
+
+impl CueSheet {
+
+    /// Read a **CUE** sheet from the given reader.
+    ///
+    /// A `TITLE`, `PERFORMER` or `INDEX` within a `FILE` but preceding its first `TRACK` belongs
+    /// to the last track of the previous file, whose `continued_indices` hold any such `INDEX`
+    /// points. A `REM` in the same position belongs to the whole sheet.
+    pub fn read<R>(mut reader: R) -> Result<Self, Error>
+        where R: BufRead,
+    {
+        let mut sheet = CueSheet::default();
+        let mut line_buffer = String::new();
+        let mut line_number = 0;
+
+        loop {
+            line_buffer.clear();
+            if reader.read_line(&mut line_buffer)? == 0 {
+                break;
+            }
+            line_number += 1;
+            let line = line_buffer.trim().trim_start_matches('\u{feff}');
+            if line.is_empty() {
+                continue;
+            }
+
+            let (command, args) = match line.find(char::is_whitespace) {
+                Some(i) => (&line[..i], line[i..].trim_start()),
+                None => (line, ""),
+            };
+            let command = command.to_ascii_uppercase();
+            let invalid = || Error::InvalidLine(line_number);
+
+            // Commands that start a new file or track.
+            match &command[..] {
+                "FILE" => {
+                    let (path, rest) = read_word(args).ok_or_else(invalid)?;
+                    let kind = rest.trim();
+                    let kind = if kind.is_empty() { "WAVE" } else { kind };
+                    sheet.files.push(File { path, kind: kind.to_string(), tracks: Vec::new() });
+                    continue;
+                },
+                "TRACK" => {
+                    let file = sheet.files.last_mut()
+                        .ok_or(Error::TrackWithoutFile(line_number))?;
+                    let (number, rest) = read_word(args).ok_or_else(invalid)?;
+                    let number = number.parse().map_err(|_| invalid())?;
+                    let kind = rest.trim().to_string();
+                    file.tracks.push(Track::new(number, kind));
+                    continue;
+                },
+                _ => (),
+            }
+
+            // Commands preceding the first track of a file that continue the previous track.
+            let continues = sheet.files.last().is_some_and(|file| file.tracks.is_empty())
+                && (command == "INDEX" || command == "TITLE" || command == "PERFORMER");
+            if continues {
+                let previous = sheet.files.len().checked_sub(2)
+                    .and_then(|i| sheet.files[i].tracks.last_mut())
+                    .ok_or(Error::CommandWithoutTrack(line_number))?;
+                match &command[..] {
+                    "INDEX" => {
+                        let index = read_index(args).ok_or_else(invalid)?;
+                        previous.continued_indices.push(index);
+                    },
+                    "TITLE" => previous.title = Some(unquote(args)),
+                    _ => previous.performer = Some(unquote(args)),
+                }
+                continue;
+            }
+
+            // Commands that apply to the current track, if any, or otherwise to the whole sheet.
+            let track = sheet.files.last_mut().and_then(|file| file.tracks.last_mut());
+
+            match (&command[..], track) {
+                ("REM", track) => {
+                    let (key, value) = match args.find(char::is_whitespace) {
+                        Some(i) => (&args[..i], unquote(args[i..].trim())),
+                        None => (args, String::new()),
+                    };
+                    let rem = match track {
+                        Some(track) => &mut track.rem,
+                        None => &mut sheet.rem,
+                    };
+                    rem.push((key.to_string(), value));
+                },
+                ("TITLE", Some(track)) => track.title = Some(unquote(args)),
+                ("TITLE", None) => sheet.title = Some(unquote(args)),
+                ("PERFORMER", Some(track)) => track.performer = Some(unquote(args)),
+                ("PERFORMER", None) => sheet.performer = Some(unquote(args)),
+                ("INDEX", Some(track)) => track.indices.push(read_index(args).ok_or_else(invalid)?),
+                (_, Some(track)) => track.other.push(line.to_string()),
+                (_, None) => sheet.other.push(line.to_string()),
+            }
+        }
+
+        Ok(sheet)
+    }
+
+    /// Attempts to read a **CUE** sheet from the specified file.
+    ///
+    /// This is a convenience constructor that opens a `File`, wraps it in a `BufReader` and then
+    /// reads the sheet from it.
+    pub fn open<P>(filename: P) -> Result<Self, Error>
+        where P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(filename)?;
+        let buf_reader = std::io::BufReader::new(file);
+        Self::read(buf_reader)
+    }
+
+    /// Attempt to write the **CUE** sheet to the given writer.
+    pub fn write<W>(&self, mut writer: W) -> Result<(), std::io::Error>
+        where W: Write,
+    {
+        let w = &mut writer;
+        write_rem(w, "", &self.rem)?;
+        for line in &self.other {
+            writeln!(w, "{}", line)?;
+        }
+        if let Some(ref performer) = self.performer {
+            writeln!(w, "PERFORMER \"{}\"", performer)?;
+        }
+        if let Some(ref title) = self.title {
+            writeln!(w, "TITLE \"{}\"", title)?;
+        }
+        let mut continued: &[Index] = &[];
+        for file in &self.files {
+            writeln!(w, "FILE \"{}\" {}", file.path, file.kind)?;
+            for index in continued {
+                writeln!(w, "    INDEX {:02} {}", index.number, index.time)?;
+            }
+            continued = file.tracks.last().map_or(&[], |track| &track.continued_indices[..]);
+            for track in &file.tracks {
+                writeln!(w, "  TRACK {:02} {}", track.number, track.kind)?;
+                if let Some(ref title) = track.title {
+                    writeln!(w, "    TITLE \"{}\"", title)?;
+                }
+                if let Some(ref performer) = track.performer {
+                    writeln!(w, "    PERFORMER \"{}\"", performer)?;
+                }
+                write_rem(w, "    ", &track.rem)?;
+                for line in &track.other {
+                    writeln!(w, "    {}", line)?;
+                }
+                for index in &track.indices {
+                    writeln!(w, "    INDEX {:02} {}", index.number, index.time)?;
+                }
+            }
+        }
+        writer.flush()
+    }
+
+    /// Convert each track into an `EntryExt` pointing to the track's file.
+    ///
+    /// The `START_ATTRIBUTE` of each entry is set to the track's `INDEX 01` (or its first index
+    /// if there is no `INDEX 01`). The `END_ATTRIBUTE` and the duration are derived from the start
+    /// of the following track in the same file. The last track of each file has no
+    /// `END_ATTRIBUTE` and an `Unknown` duration. A track followed by one that starts earlier also
+    /// has an `Unknown` duration.
+    ///
+    /// A track with a continued `INDEX 01` instead points to the following file, starting at that
+    /// index and ending at the start of the following file's first track.
+    ///
+    /// The name of each entry is the track's title and the track's performer (or otherwise the
+    /// sheet's performer) is stored in the `CREATOR_ATTRIBUTE`.
+    pub fn to_entry_exts(&self) -> Vec<EntryExt> {
+        let mut entry_exts = Vec::new();
+        for (f, file) in self.files.iter().enumerate() {
+            let starts: Vec<Time> = file.tracks.iter().map(Track::start).collect();
+            for (i, track) in file.tracks.iter().enumerate() {
+                let continued = track.continued_indices.iter().find(|index| index.number == 1);
+                let (path, start, end) = match (continued, self.files.get(f + 1)) {
+                    (Some(index), Some(next)) =>
+                        (&next.path, index.time, next.tracks.first().map(Track::start)),
+                    _ => (&file.path, starts[i], starts.get(i + 1).cloned()),
+                };
+                // Tracks that are out of order have no meaningful duration.
+                let duration = match end {
+                    Some(end) if end >= start => {
+                        Duration::from_secs_f64(end.as_secs() - start.as_secs())
+                    },
+                    _ => Duration::Unknown,
+                };
+                let name = track.title.clone().unwrap_or_default();
+                let mut extinf = ExtInf::new(duration, name);
+                if let Some(performer) = track.performer.as_ref().or(self.performer.as_ref()) {
+                    extinf.set_attribute(CREATOR_ATTRIBUTE, &performer[..]);
+                }
+                extinf.set_attribute(START_ATTRIBUTE, start.as_secs().to_string());
+                if let Some(end) = end {
+                    extinf.set_attribute(END_ATTRIBUTE, end.as_secs().to_string());
+                }
+                let entry = Entry::Path(path.clone().into());
+                entry_exts.push(EntryExt { entry, extinf });
+            }
+        }
+        entry_exts
+    }
+
+    /// Produce a **CUE** sheet from the given entries.
+    ///
+    /// Consecutive entries that point to the same file are grouped into a single `FILE`, with one
+    /// `TRACK` per entry starting at the entry's `START_ATTRIBUTE` (or the start of the file if it
+    /// has none). Tracks are numbered sequentially from `1` across the whole sheet.
+    pub fn from_entry_exts<'a, I>(entry_exts: I) -> Self
+        where I: IntoIterator<Item = &'a EntryExt>,
+    {
+        let mut sheet = CueSheet::default();
+        let mut number = 0;
+        for entry_ext in entry_exts {
+            let path = match entry_ext.entry {
+                Entry::Path(ref path) => path.display().to_string(),
+                Entry::Url(ref url) | Entry::Uri(ref url) => url.to_string(),
+            };
+            let new_file = match sheet.files.last() {
+                Some(file) => file.path != path,
+                None => true,
+            };
+            if new_file {
+                let kind = file_kind(&path).to_string();
+                sheet.files.push(File { path, kind, tracks: Vec::new() });
+            }
+
+            number += 1;
+            let extinf = &entry_ext.extinf;
+            let mut track = Track::new(number, "AUDIO".to_string());
+            if !extinf.name.is_empty() {
+                track.title = Some(extinf.name.clone());
+            }
+            track.performer = extinf.attribute(CREATOR_ATTRIBUTE).map(str::to_string);
+            let start = offsets(extinf).map(|(start, _)| start).unwrap_or(0.0);
+            track.indices.push(Index { number: 1, time: Time::from_secs(start) });
+            sheet.files.last_mut().expect("a file was pushed").tracks.push(track);
+        }
+        sheet
+    }
+
+}
+
+impl Track {
+
+    /// Create a track with the given number and type and no other information.
+    pub fn new(number: u32, kind: String) -> Self {
+        Track {
+            number,
+            kind,
+            title: None,
+            performer: None,
+            rem: Vec::new(),
+            indices: Vec::new(),
+            continued_indices: Vec::new(),
+            other: Vec::new(),
+        }
+    }
+
+    /// The offset at which the track starts: its `INDEX 01`, otherwise its first index, otherwise
+    /// the start of the file.
+    pub fn start(&self) -> Time {
+        self.indices.iter().find(|i| i.number == 1)
+            .or_else(|| self.indices.first())
+            .map(|i| i.time)
+            .unwrap_or_default()
+    }
+
+}
+
+impl Time {
+
+    /// Create a `Time` from the given number of minutes, seconds and frames.
+    ///
+    /// **Panics** if the number of frames does not fit a `u64`. See `checked_new`.
+    pub fn new(minutes: u64, seconds: u64, frames: u64) -> Self {
+        Self::checked_new(minutes, seconds, frames).expect("the time overflows a `u64` of frames")
+    }
+
+    /// Create a `Time` from the given number of minutes, seconds and frames, returning `None` if
+    /// the number of frames does not fit a `u64`.
+    pub fn checked_new(minutes: u64, seconds: u64, frames: u64) -> Option<Self> {
+        let fps = FRAMES_PER_SEC as u64;
+        let frames = minutes.checked_mul(60)?
+            .checked_add(seconds)?
+            .checked_mul(fps)?
+            .checked_add(frames)?;
+        Some(Time { frames })
+    }
+
+    /// Create a `Time` from the given number of seconds, rounded to the nearest frame.
+    pub fn from_secs(secs: f64) -> Self {
+        Time { frames: (secs.max(0.0) * FRAMES_PER_SEC as f64).round() as u64 }
+    }
+
+    /// The offset in seconds.
+    pub fn as_secs(&self) -> f64 {
+        self.frames as f64 / FRAMES_PER_SEC as f64
+    }
+
+}
+
+
+/// The `START_ATTRIBUTE` and `END_ATTRIBUTE` offsets in seconds of the given `ExtInf`, if it has a
+/// valid `START_ATTRIBUTE`.
+pub fn offsets(extinf: &ExtInf) -> Option<(f64, Option<f64>)> {
+    let start = extinf.attribute(START_ATTRIBUTE)?.parse().ok()?;
+    let end = extinf.attribute(END_ATTRIBUTE).and_then(|end| end.parse().ok());
+    Some((start, end))
+}
+
+
+/// Read a single, possibly quoted, word from the start of the given arguments.
+///
+/// Returns the word along with the remainder of the arguments.
+fn read_word(args: &str) -> Option<(String, &str)> {
+    let args = args.trim_start();
+    if let Some(quoted) = args.strip_prefix('"') {
+        let end = quoted.find('"')?;
+        Some((quoted[..end].to_string(), &quoted[end + 1..]))
+    } else {
+        let end = args.find(char::is_whitespace).unwrap_or(args.len());
+        if end == 0 {
+            return None;
+        }
+        Some((args[..end].to_string(), &args[end..]))
+    }
+}
+
+/// Read the number and time of an `INDEX` from the given arguments.
+fn read_index(args: &str) -> Option<Index> {
+    let (number, rest) = read_word(args)?;
+    let number = number.parse().ok()?;
+    let time = rest.trim().parse().ok()?;
+    Some(Index { number, time })
+}
+
+/// Strip the surrounding quotes from the given value, if any.
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+        Some(unquoted) => unquoted.to_string(),
+        None => value.to_string(),
+    }
+}
+
+/// Write the given `REM` comments, quoting values that contain whitespace.
+fn write_rem<W>(writer: &mut W, indent: &str, rem: &[(String, String)])
+    -> Result<(), std::io::Error>
+    where W: Write,
+{
+    for (key, value) in rem {
+        if value.is_empty() {
+            writeln!(writer, "{}REM {}", indent, key)?;
+        } else if value.contains(char::is_whitespace) {
+            writeln!(writer, "{}REM {} \"{}\"", indent, key, value)?;
+        } else {
+            writeln!(writer, "{}REM {} {}", indent, key, value)?;
+        }
+    }
+    Ok(())
+}
+
+/// The `FILE` type for the given path based on its extension.
+fn file_kind(path: &str) -> &'static str {
+    let extension = path.rsplit('.').next().unwrap_or("").to_ascii_lowercase();
+    match &extension[..] {
+        "mp3" => "MP3",
+        "aif" | "aiff" => "AIFF",
+        _ => "WAVE",
+    }
+}
+
+
+impl std::str::FromStr for Time {
+    type Err = ParseTimeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(3, ':');
+        let mut next = || parts.next().unwrap_or("").parse().map_err(|_| ParseTimeError);
+        let (minutes, seconds, frames) = (next()?, next()?, next()?);
+        Time::checked_new(minutes, seconds, frames).ok_or(ParseTimeError)
+    }
+}
+
+impl std::fmt::Display for Time {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let fps = FRAMES_PER_SEC as u64;
+        let secs = self.frames / fps;
+        write!(f, "{:02}:{:02}:{:02}", secs / 60, secs % 60, self.frames % fps)
+    }
+}
+
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::BufRead(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::BufRead(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::InvalidLine(line) => write!(f, "line {} is malformed", line),
+            Error::TrackWithoutFile(line) =>
+                write!(f, "the `TRACK` on line {} does not belong to a `FILE`", line),
+            Error::CommandWithoutTrack(line) =>
+                write!(f, "the command on line {} precedes the first `TRACK` of its `FILE`", line),
+            Error::BufRead(ref err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ParseTimeError {}
+
+impl std::fmt::Display for ParseTimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "the time is not a valid `mm:ss:ff` offset")
+    }
+}
@@ -0,0 +1,433 @@
+use {cue, pls, read};
+#[cfg(feature = "xml")]
+use {asx, wpl, xspf};
+use {Duration, Entry, EntryExt, ReadEntryExtError};
+use std;
+use std::io::{Read, Write};
+use write;
+
+/// The playlist formats that may be detected by `detect_format`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// The original, non-extended **M3U** format: one entry per line.
+    M3u,
+    /// The extended **M3U** format beginning with the `#EXTM3U` header.
+    M3uExt,
+    /// An HLS media playlist: extended **M3U** with `#EXT-X-` tags describing media segments.
+    HlsMedia,
+    /// An HLS master playlist: extended **M3U** listing variant streams via `#EXT-X-STREAM-INF`.
+    HlsMaster,
+    /// The **PLS** format.
+    Pls,
+    /// The **XSPF** format.
+    Xspf,
+    /// The Windows Media **ASX** format.
+    Asx,
+    /// The Windows Media **WPL** format.
+    Wpl,
+    /// A **CUE** sheet.
+    Cue,
+}
+
+/// A playlist read by `open_any` or `read_any`, regardless of the format in which it was stored.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Playlist {
+    /// The format from which the playlist was read.
+    pub format: Format,
+    /// The title of the playlist, for formats that have one.
+    pub title: Option<String>,
+    /// The entries of the playlist.
+    ///
+    /// Entries without extra information, such as those of a plain **M3U** playlist, have an
+    /// `Unknown` duration and an empty name.
+    pub entries: Vec<EntryExt>,
+}
+
+/// Errors that may occur when reading a playlist of any format.
+#[derive(Debug)]
+pub enum OpenError {
+    /// The content does not appear to be a playlist of any supported format.
+    UnknownFormat,
+    /// The content is a playlist of a format whose support is disabled, i.e. **XSPF**, **ASX** or
+    /// **WPL** without the `xml` feature.
+    UnsupportedFormat(Format),
+    /// Errors produced when reading an **M3U** playlist or opening a file.
+    Io(std::io::Error),
+    /// Errors produced when reading a **PLS** playlist.
+    Pls(pls::Error),
+    /// Errors produced when reading an **XSPF** playlist.
+    #[cfg(feature = "xml")]
+    Xspf(xspf::Error),
+    /// Errors produced when reading an **ASX** playlist.
+    #[cfg(feature = "xml")]
+    Asx(asx::Error),
+    /// Errors produced when reading a **WPL** playlist.
+    #[cfg(feature = "xml")]
+    Wpl(wpl::Error),
+    /// Errors produced when reading a **CUE** sheet.
+    Cue(cue::Error),
+}
+
+
+impl Format {
+
+    /// Whether or not the format is one of the **M3U** based formats, including HLS.
+    pub fn is_m3u(&self) -> bool {
+        match *self {
+            Format::M3u | Format::M3uExt | Format::HlsMedia | Format::HlsMaster => true,
+            Format::Pls | Format::Xspf | Format::Asx | Format::Wpl | Format::Cue => false,
+        }
+    }
+
+}
+
+
+impl Playlist {
+
+    /// Write the playlist to the given writer in the given format.
+    ///
+    /// The `HlsMedia` and `HlsMaster` formats are written as `M3uExt`, as the HLS tags are not
+    /// retained when reading. Writing a playlist containing `Url` entries as a `Cue` sheet
+    /// fails with an `InvalidInput` error as a **CUE** sheet may only reference files.
+    pub fn write<W>(&self, format: Format, writer: W) -> Result<(), std::io::Error>
+        where W: Write,
+    {
+        let entries = &self.entries;
+        match format {
+            Format::M3u => {
+                let mut writer = write::EntryWriter::new(writer);
+                for entry_ext in entries {
+                    writer.write_entry(&entry_ext.entry)?;
+                }
+                writer.flush()
+            },
+            Format::M3uExt | Format::HlsMedia | Format::HlsMaster => {
+                let mut writer = write::EntryExtWriter::new_ext(writer)?;
+                for entry_ext in entries {
+                    writer.write_entry(entry_ext)?;
+                }
+                writer.flush()
+            },
+            Format::Pls => {
+                let mut writer = pls::Writer::new(writer)?;
+                for entry_ext in entries {
+                    writer.write_entry_ext(entry_ext)?;
+                }
+                writer.finish()
+            },
+            #[cfg(feature = "xml")]
+            Format::Xspf => {
+                let metadata = xspf::Metadata {
+                    title: self.title.clone(),
+                    ..Default::default()
+                };
+                let mut writer = xspf::Writer::with_metadata(writer, &metadata)?;
+                for entry_ext in entries {
+                    writer.write_entry_ext(entry_ext)?;
+                }
+                writer.finish()
+            },
+            #[cfg(feature = "xml")]
+            Format::Asx => {
+                let mut writer = asx::Writer::with_title(writer, self.title.as_deref())?;
+                for entry_ext in entries {
+                    writer.write_entry_ext(entry_ext)?;
+                }
+                writer.finish()
+            },
+            #[cfg(feature = "xml")]
+            Format::Wpl => {
+                let mut writer = wpl::Writer::with_title(writer, self.title.as_deref())?;
+                for entry_ext in entries {
+                    writer.write_entry_ext(entry_ext)?;
+                }
+                writer.finish()
+            },
+            #[cfg(not(feature = "xml"))]
+            Format::Xspf | Format::Asx | Format::Wpl => {
+                let msg = format!("writing the {:?} format requires the `xml` feature", format);
+                Err(std::io::Error::new(std::io::ErrorKind::Unsupported, msg))
+            },
+            Format::Cue => {
+                if let Some(entry_ext) = entries.iter().find(|e| !e.entry.is_path()) {
+                    let location = match entry_ext.entry {
+                        Entry::Url(ref url) | Entry::Uri(ref url) => url.to_string(),
+                        Entry::Path(ref path) => path.display().to_string(),
+                    };
+                    let msg = format!("`{}` cannot be referenced by a CUE sheet", location);
+                    return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
+                }
+                let mut sheet = cue::CueSheet::from_entry_exts(entries);
+                sheet.title = self.title.clone();
+                sheet.write(writer)
+            },
+        }
+    }
+
+}
+
+
+/// Detect the format of a playlist from its content.
+///
+/// Only the content is inspected, not the file extension. Textual content that does not match
+/// any other format is assumed to be a plain **M3U** playlist.
+///
+/// Returns `None` if the content is empty or appears to be binary.
+pub fn detect_format(bytes: &[u8]) -> Option<Format> {
+    if bytes.contains(&0) {
+        return None;
+    }
+    let text = String::from_utf8_lossy(bytes);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+    if text.is_empty() {
+        return None;
+    }
+
+    if text.starts_with("#EXTM3U") {
+        let format = if text.contains("#EXT-X-STREAM-INF") {
+            Format::HlsMaster
+        } else if text.contains("#EXT-X-") {
+            Format::HlsMedia
+        } else {
+            Format::M3uExt
+        };
+        return Some(format);
+    }
+
+    if text.starts_with('<') {
+        return detect_markup(text);
+    }
+
+    let first_line = text.lines().next().unwrap_or("").trim();
+    if first_line.eq_ignore_ascii_case("[playlist]") {
+        return Some(Format::Pls);
+    }
+
+    // A CUE sheet has at least one `FILE "path" TYPE` and one `TRACK nn AUDIO` command.
+    if text.lines().any(is_cue_file) && text.lines().any(is_cue_track) {
+        return Some(Format::Cue);
+    }
+
+    Some(Format::M3u)
+}
+
+/// Read a playlist of any supported format from the given reader.
+///
+/// The whole content is read before its format is detected via `detect_format`.
+pub fn read_any<R>(mut reader: R) -> Result<Playlist, OpenError>
+    where R: Read,
+{
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    let format = detect_format(&bytes).ok_or(OpenError::UnknownFormat)?;
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&bytes);
+
+    let (title, entries) = match format {
+        Format::M3u => {
+            let mut reader = read::EntryReader::new(bytes);
+            let mut entries = Vec::new();
+            for entry in reader.entries() {
+                entries.push(entry?.extend(Duration::Unknown, ""));
+            }
+            (None, entries)
+        },
+        Format::M3uExt | Format::HlsMedia | Format::HlsMaster => {
+            let mut reader = read::EntryExtReader::new_ext(bytes)
+                .map_err(|_| OpenError::UnknownFormat)?;
+            let mut entries = Vec::new();
+            for entry_ext in reader.entry_exts() {
+                // Entries without an `#EXTINF:` tag, such as HLS variant streams, are kept.
+                let entry_ext = match entry_ext {
+                    Ok(entry_ext) => entry_ext,
+                    Err(ReadEntryExtError::ExtInfNotFound(entry)) => {
+                        entry.extend(Duration::Unknown, "")
+                    },
+                    Err(ReadEntryExtError::BufRead(err)) => return Err(err.into()),
+                };
+                entries.push(entry_ext);
+            }
+            (None, entries)
+        },
+        Format::Pls => {
+            let mut reader = pls::Reader::new(bytes)?;
+            (None, reader.entry_exts().collect())
+        },
+        #[cfg(feature = "xml")]
+        Format::Xspf => {
+            let mut reader = xspf::Reader::new(bytes)?;
+            let title = reader.metadata().title.clone();
+            (title, reader.entry_exts().collect())
+        },
+        #[cfg(feature = "xml")]
+        Format::Asx => {
+            let mut reader = asx::Reader::new(bytes)?;
+            let title = reader.title().map(str::to_string);
+            (title, reader.entry_exts().collect())
+        },
+        #[cfg(feature = "xml")]
+        Format::Wpl => {
+            let mut reader = wpl::Reader::new(bytes)?;
+            let title = reader.title().map(str::to_string);
+            (title, reader.entry_exts().collect())
+        },
+        #[cfg(not(feature = "xml"))]
+        Format::Xspf | Format::Asx | Format::Wpl => {
+            return Err(OpenError::UnsupportedFormat(format));
+        },
+        Format::Cue => {
+            let sheet = cue::CueSheet::read(bytes)?;
+            let entries = sheet.to_entry_exts();
+            (sheet.title, entries)
+        },
+    };
+
+    Ok(Playlist { format, title, entries })
+}
+
+/// Attempt to read a playlist of any supported format from the specified file.
+///
+/// This is a convenience function that opens a `File` and reads the playlist from it via
+/// `read_any`.
+pub fn open_any<P>(filename: P) -> Result<Playlist, OpenError>
+    where P: AsRef<std::path::Path>,
+{
+    let file = std::fs::File::open(filename)?;
+    read_any(file)
+}
+
+
+/// Whether or not the given line is a **CUE** `FILE` command with a quoted path and a type.
+fn is_cue_file(line: &str) -> bool {
+    let line = line.trim();
+    let rest = match line.get(..4) {
+        Some(command) if command.eq_ignore_ascii_case("FILE") => line[4..].trim_start(),
+        _ => return false,
+    };
+    let kind = match rest.strip_prefix('"').and_then(|path| path.find('"')) {
+        Some(end) => rest[end + 2..].trim(),
+        None => return false,
+    };
+    !kind.is_empty() && !kind.contains(char::is_whitespace)
+}
+
+/// Whether or not the given line is a **CUE** `TRACK nn AUDIO` command.
+fn is_cue_track(line: &str) -> bool {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    match words[..] {
+        [command, number, kind] => command.eq_ignore_ascii_case("TRACK")
+            && !number.is_empty()
+            && number.bytes().all(|b| b.is_ascii_digit())
+            && kind.eq_ignore_ascii_case("AUDIO"),
+        _ => false,
+    }
+}
+
+/// Detect the XML based format from the name of the root element.
+fn detect_markup(mut text: &str) -> Option<Format> {
+    loop {
+        text = text.trim_start();
+        if text.starts_with("<?wpl") {
+            return Some(Format::Wpl);
+        }
+        // Skip the XML declaration, processing instructions, comments and the doctype.
+        let end = if text.starts_with("<?") {
+            text.find("?>").map(|i| i + 2)
+        } else if text.starts_with("<!--") {
+            text.find("-->").map(|i| i + 3)
+        } else if text.starts_with("<!") {
+            text.find('>').map(|i| i + 1)
+        } else {
+            break;
+        };
+        text = &text[end?..];
+    }
+
+    let name_end = text.find(|c: char| c.is_whitespace() || c == '>' || c == '/')?;
+    let name = &text[1..name_end];
+    let local_name = name.rsplit(':').next().unwrap_or(name);
+    if local_name.eq_ignore_ascii_case("playlist") {
+        Some(Format::Xspf)
+    } else if local_name.eq_ignore_ascii_case("asx") {
+        Some(Format::Asx)
+    } else if local_name.eq_ignore_ascii_case("smil") {
+        Some(Format::Wpl)
+    } else {
+        None
+    }
+}
+
+
+impl From<std::io::Error> for OpenError {
+    fn from(err: std::io::Error) -> Self {
+        OpenError::Io(err)
+    }
+}
+
+impl From<pls::Error> for OpenError {
+    fn from(err: pls::Error) -> Self {
+        OpenError::Pls(err)
+    }
+}
+
+#[cfg(feature = "xml")]
+impl From<xspf::Error> for OpenError {
+    fn from(err: xspf::Error) -> Self {
+        OpenError::Xspf(err)
+    }
+}
+
+#[cfg(feature = "xml")]
+impl From<asx::Error> for OpenError {
+    fn from(err: asx::Error) -> Self {
+        OpenError::Asx(err)
+    }
+}
+
+#[cfg(feature = "xml")]
+impl From<wpl::Error> for OpenError {
+    fn from(err: wpl::Error) -> Self {
+        OpenError::Wpl(err)
+    }
+}
+
+impl From<cue::Error> for OpenError {
+    fn from(err: cue::Error) -> Self {
+        OpenError::Cue(err)
+    }
+}
+
+impl std::error::Error for OpenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            OpenError::UnknownFormat | OpenError::UnsupportedFormat(_) => None,
+            OpenError::Io(ref err) => Some(err),
+            OpenError::Pls(ref err) => Some(err),
+            #[cfg(feature = "xml")]
+            OpenError::Xspf(ref err) => Some(err),
+            #[cfg(feature = "xml")]
+            OpenError::Asx(ref err) => Some(err),
+            #[cfg(feature = "xml")]
+            OpenError::Wpl(ref err) => Some(err),
+            OpenError::Cue(ref err) => Some(err),
+        }
+    }
+}
+
+impl std::fmt::Display for OpenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            OpenError::UnknownFormat => write!(f, "the playlist format could not be detected"),
+            OpenError::UnsupportedFormat(format) =>
+                write!(f, "reading the {:?} format requires the `xml` feature", format),
+            OpenError::Io(ref err) => err.fmt(f),
+            OpenError::Pls(ref err) => err.fmt(f),
+            #[cfg(feature = "xml")]
+            OpenError::Xspf(ref err) => err.fmt(f),
+            #[cfg(feature = "xml")]
+            OpenError::Asx(ref err) => err.fmt(f),
+            #[cfg(feature = "xml")]
+            OpenError::Wpl(ref err) => err.fmt(f),
+            OpenError::Cue(ref err) => err.fmt(f),
+        }
+    }
+}
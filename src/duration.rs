@@ -0,0 +1,117 @@
+use std;
+
+/// The duration of an entry's media.
+///
+/// Extended **M3U** and **PLS** playlists write a duration of `-1` for live streams and for media
+/// of unknown duration alike. When read, a negative duration is taken to mark a `Live` stream,
+/// while `Unknown` is used where a playlist provides no duration at all, e.g. for the entries of a
+/// plain **M3U** playlist.
+///
+/// ```
+/// extern crate m3u;
+///
+/// use m3u::Duration;
+///
+/// fn main() {
+///     assert_eq!("123".parse(), Ok(Duration::from_secs(123)));
+///     assert_eq!("1.5".parse(), Ok(Duration::Known(std::time::Duration::from_millis(1_500))));
+///     assert_eq!("-1".parse(), Ok(Duration::Live));
+///     assert_eq!(Duration::Live.to_string(), "-1");
+/// }
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Duration {
+    /// The duration of the media is known.
+    Known(std::time::Duration),
+    /// The media is a live stream or is otherwise of indefinite duration.
+    Live,
+    /// The duration of the media is not known.
+    #[default]
+    Unknown,
+}
+
+
+impl Duration {
+
+    /// A known duration of the given number of whole seconds.
+    pub fn from_secs(secs: u64) -> Self {
+        Duration::Known(std::time::Duration::from_secs(secs))
+    }
+
+    /// The duration for the given number of seconds as written within a playlist.
+    ///
+    /// Negative and infinite values produce `Live`, `NaN` produces `Unknown`.
+    pub fn from_secs_f64(secs: f64) -> Self {
+        if secs.is_nan() {
+            return Duration::Unknown;
+        }
+        match std::time::Duration::try_from_secs_f64(secs) {
+            Ok(duration) => Duration::Known(duration),
+            Err(_) => Duration::Live,
+        }
+    }
+
+    /// The known duration, if there is one.
+    pub fn known(&self) -> Option<std::time::Duration> {
+        match *self {
+            Duration::Known(duration) => Some(duration),
+            Duration::Live | Duration::Unknown => None,
+        }
+    }
+
+    /// The known duration in seconds, if there is one.
+    pub fn as_secs_f64(&self) -> Option<f64> {
+        self.known().map(|duration| duration.as_secs_f64())
+    }
+
+    /// The duration in seconds as written within an extended **M3U** or **PLS** playlist: the
+    /// known duration, or `-1` if it is `Live` or `Unknown`.
+    pub fn to_playlist_secs(&self) -> f64 {
+        self.as_secs_f64().unwrap_or(-1.0)
+    }
+
+    /// Whether or not the duration is `Known`.
+    pub fn is_known(&self) -> bool {
+        self.known().is_some()
+    }
+
+    /// Whether or not the media is `Live`.
+    pub fn is_live(&self) -> bool {
+        *self == Duration::Live
+    }
+
+    /// Whether or not the duration is `Unknown`.
+    pub fn is_unknown(&self) -> bool {
+        *self == Duration::Unknown
+    }
+
+}
+
+
+impl From<std::time::Duration> for Duration {
+    fn from(duration: std::time::Duration) -> Self {
+        Duration::Known(duration)
+    }
+}
+
+impl From<f64> for Duration {
+    /// Equivalent to `Duration::from_secs_f64`.
+    fn from(secs: f64) -> Self {
+        Duration::from_secs_f64(secs)
+    }
+}
+
+impl std::str::FromStr for Duration {
+    type Err = std::num::ParseFloatError;
+    /// Parses an integer or decimal number of seconds via `Duration::from_secs_f64`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.trim().parse().map(Duration::from_secs_f64)
+    }
+}
+
+impl std::fmt::Display for Duration {
+    /// Formats the duration in seconds as written by `to_playlist_secs`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.to_playlist_secs())
+    }
+}
@@ -0,0 +1,346 @@
+use {Entry, EntryExt, ExtInf};
+use std;
+
+/// An in-memory playlist of `EntryExt`s in which every modification is recorded as an invertible
+/// `Edit`.
+///
+/// Edits may be undone and redone any number of times. Applying a new edit clears the redo stack.
+///
+/// Listeners registered via `on_change` are notified of every `Change` made to the playlist,
+/// including those made while undoing and redoing, allowing views to update incrementally.
+pub struct Editor {
+    /// The current state of the playlist.
+    entries: Vec<EntryExt>,
+    /// Edits that have been applied, most recent last.
+    undo_stack: Vec<Edit>,
+    /// Edits that have been undone, most recently undone last.
+    redo_stack: Vec<Edit>,
+    /// Callbacks notified of each change to `entries`.
+    listeners: Vec<Listener>,
+}
+
+/// A callback notified of changes to the playlist of an `Editor`.
+type Listener = Box<dyn FnMut(&Change)>;
+
+/// A single invertible modification to an `Editor`'s playlist.
+///
+/// Each variant stores enough information to produce its own inverse.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Edit {
+    /// The `entry` was inserted at `index`.
+    Insert {
+        /// The index at which the entry was inserted.
+        index: usize,
+        /// The inserted entry.
+        entry: EntryExt,
+    },
+    /// The `entry` was removed from `index`.
+    Remove {
+        /// The index from which the entry was removed.
+        index: usize,
+        /// The removed entry.
+        entry: EntryExt,
+    },
+    /// The entry at `from` was moved so that it now resides at `to`.
+    Move {
+        /// The index of the entry before the move.
+        from: usize,
+        /// The index of the entry after the move.
+        to: usize,
+    },
+    /// The `Entry` at `index` was replaced.
+    SetEntry {
+        /// The index of the modified entry.
+        index: usize,
+        /// The `Entry` before the edit.
+        old: Entry,
+        /// The `Entry` after the edit.
+        new: Entry,
+    },
+    /// The `ExtInf` at `index` was replaced.
+    SetExtInf {
+        /// The index of the modified entry.
+        index: usize,
+        /// The `ExtInf` before the edit.
+        old: ExtInf,
+        /// The `ExtInf` after the edit.
+        new: ExtInf,
+    },
+    /// A sequence of edits that are undone and redone as a single step.
+    Batch(Vec<Edit>),
+}
+
+/// A notification describing a change to the playlist of an `Editor`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// An entry was inserted at the given index.
+    Inserted(usize),
+    /// The entry at the given index was removed.
+    Removed(usize),
+    /// The entry at `from` was moved to `to`.
+    Moved {
+        /// The index of the entry before the move.
+        from: usize,
+        /// The index of the entry after the move.
+        to: usize,
+    },
+    /// The entry at the given index was modified in place.
+    Updated(usize),
+}
+
+/// Errors that may occur when editing the playlist of an `Editor`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EditError {
+    /// The given index was out of bounds of the playlist.
+    IndexOutOfBounds {
+        /// The invalid index.
+        index: usize,
+        /// The length of the playlist at the time of the edit.
+        len: usize,
+    },
+}
+
+
+impl Editor {
+
+    /// Create an `Editor` for the given playlist with empty undo and redo stacks.
+    pub fn new(entries: Vec<EntryExt>) -> Self {
+        Editor {
+            entries,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            listeners: Vec::new(),
+        }
+    }
+
+    /// The current state of the playlist.
+    pub fn entries(&self) -> &[EntryExt] {
+        &self.entries
+    }
+
+    /// Produce the current state of the playlist, discarding all history.
+    pub fn into_entries(self) -> Vec<EntryExt> {
+        self.entries
+    }
+
+    /// Register a callback that is notified of every `Change` made to the playlist.
+    pub fn on_change<F>(&mut self, listener: F)
+        where F: 'static + FnMut(&Change),
+    {
+        self.listeners.push(Box::new(listener));
+    }
+
+    /// Insert the `entry` at `index`, shifting all following entries down.
+    ///
+    /// An `index` equal to the length of the playlist appends the entry.
+    pub fn insert(&mut self, index: usize, entry: EntryExt) -> Result<(), EditError> {
+        let len = self.entries.len();
+        if index > len {
+            return Err(EditError::IndexOutOfBounds { index, len });
+        }
+        self.record(Edit::Insert { index, entry });
+        Ok(())
+    }
+
+    /// Append the `entry` to the end of the playlist.
+    pub fn push(&mut self, entry: EntryExt) {
+        let index = self.entries.len();
+        self.record(Edit::Insert { index, entry });
+    }
+
+    /// Remove the entry at `index`.
+    pub fn remove(&mut self, index: usize) -> Result<(), EditError> {
+        check_index(index, self.entries.len())?;
+        let entry = self.entries[index].clone();
+        self.record(Edit::Remove { index, entry });
+        Ok(())
+    }
+
+    /// Move the entry at `from` so that it resides at `to`.
+    pub fn move_entry(&mut self, from: usize, to: usize) -> Result<(), EditError> {
+        check_index(from, self.entries.len())?;
+        check_index(to, self.entries.len())?;
+        self.record(Edit::Move { from, to });
+        Ok(())
+    }
+
+    /// Replace the `Entry` at `index`, leaving its `ExtInf` untouched.
+    pub fn set_entry(&mut self, index: usize, entry: Entry) -> Result<(), EditError> {
+        check_index(index, self.entries.len())?;
+        let old = self.entries[index].entry.clone();
+        self.record(Edit::SetEntry { index, old, new: entry });
+        Ok(())
+    }
+
+    /// Replace the `ExtInf` at `index`, leaving its `Entry` untouched.
+    pub fn set_extinf(&mut self, index: usize, extinf: ExtInf) -> Result<(), EditError> {
+        check_index(index, self.entries.len())?;
+        let old = self.entries[index].extinf.clone();
+        self.record(Edit::SetExtInf { index, old, new: extinf });
+        Ok(())
+    }
+
+    /// Apply all edits made within `f` as a single undoable step.
+    ///
+    /// If `f` returns an error, all edits made within `f` are reverted and the error is returned.
+    pub fn batch<F>(&mut self, f: F) -> Result<(), EditError>
+        where F: FnOnce(&mut Self) -> Result<(), EditError>,
+    {
+        let start = self.undo_stack.len();
+        let redo_stack = std::mem::take(&mut self.redo_stack);
+        let result = f(self);
+        let start = std::cmp::min(start, self.undo_stack.len());
+        let edits = self.undo_stack.split_off(start);
+        match result {
+            Ok(()) => {
+                if edits.is_empty() {
+                    self.redo_stack = redo_stack;
+                } else {
+                    self.undo_stack.push(Edit::Batch(edits));
+                }
+                Ok(())
+            },
+            Err(err) => {
+                for edit in edits.into_iter().rev() {
+                    self.apply(edit.inverse());
+                }
+                self.redo_stack = redo_stack;
+                Err(err)
+            },
+        }
+    }
+
+    /// Whether or not there are any edits that may be undone.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    /// Whether or not there are any undone edits that may be redone.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Undo the most recent edit.
+    ///
+    /// Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        match self.undo_stack.pop() {
+            None => false,
+            Some(edit) => {
+                self.apply(edit.inverse());
+                self.redo_stack.push(edit);
+                true
+            },
+        }
+    }
+
+    /// Redo the most recently undone edit.
+    ///
+    /// Returns `false` if there was nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        match self.redo_stack.pop() {
+            None => false,
+            Some(edit) => {
+                self.apply(edit.clone());
+                self.undo_stack.push(edit);
+                true
+            },
+        }
+    }
+
+    /// Discard all undo and redo history, keeping the current state of the playlist.
+    pub fn clear_history(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+    }
+
+    /// Apply the given edit, push it onto the undo stack and clear the redo stack.
+    fn record(&mut self, edit: Edit) {
+        self.apply(edit.clone());
+        self.undo_stack.push(edit);
+        self.redo_stack.clear();
+    }
+
+    /// Apply the given edit to the playlist, notifying all listeners.
+    ///
+    /// The edit's indices are assumed to have already been validated.
+    fn apply(&mut self, edit: Edit) {
+        let change = match edit {
+            Edit::Insert { index, entry } => {
+                self.entries.insert(index, entry);
+                Change::Inserted(index)
+            },
+            Edit::Remove { index, .. } => {
+                self.entries.remove(index);
+                Change::Removed(index)
+            },
+            Edit::Move { from, to } => {
+                let entry = self.entries.remove(from);
+                self.entries.insert(to, entry);
+                Change::Moved { from, to }
+            },
+            Edit::SetEntry { index, new, .. } => {
+                self.entries[index].entry = new;
+                Change::Updated(index)
+            },
+            Edit::SetExtInf { index, new, .. } => {
+                self.entries[index].extinf = new;
+                Change::Updated(index)
+            },
+            Edit::Batch(edits) => {
+                for edit in edits {
+                    self.apply(edit);
+                }
+                return;
+            },
+        };
+        for listener in &mut self.listeners {
+            listener(&change);
+        }
+    }
+
+}
+
+impl Edit {
+
+    /// Produce the edit that reverts this edit.
+    pub fn inverse(&self) -> Edit {
+        match *self {
+            Edit::Insert { index, ref entry } =>
+                Edit::Remove { index, entry: entry.clone() },
+            Edit::Remove { index, ref entry } =>
+                Edit::Insert { index, entry: entry.clone() },
+            Edit::Move { from, to } =>
+                Edit::Move { from: to, to: from },
+            Edit::SetEntry { index, ref old, ref new } =>
+                Edit::SetEntry { index, old: new.clone(), new: old.clone() },
+            Edit::SetExtInf { index, ref old, ref new } =>
+                Edit::SetExtInf { index, old: new.clone(), new: old.clone() },
+            Edit::Batch(ref edits) =>
+                Edit::Batch(edits.iter().rev().map(Edit::inverse).collect()),
+        }
+    }
+
+}
+
+
+/// Ensure that `index` is less than `len`.
+fn check_index(index: usize, len: usize) -> Result<(), EditError> {
+    if index < len {
+        Ok(())
+    } else {
+        Err(EditError::IndexOutOfBounds { index, len })
+    }
+}
+
+
+impl std::error::Error for EditError {}
+
+impl std::fmt::Display for EditError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            EditError::IndexOutOfBounds { index, len } =>
+                write!(f, "index {} is out of bounds for a playlist of length {}", index, len),
+        }
+    }
+}
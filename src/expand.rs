@@ -0,0 +1,261 @@
+use {Duration, Entry, EntryExt};
+use std;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The file extensions of media selected by an `Expander` by default.
+pub const DEFAULT_MEDIA_EXTENSIONS: &[&str] = &[
+    "aac", "aif", "aiff", "ape", "avi", "flac", "m4a", "m4v", "mka", "mkv", "mov", "mp2", "mp3",
+    "mp4", "mpc", "oga", "ogg", "ogv", "opus", "wav", "webm", "wma", "wmv", "wv",
+];
+
+/// Replaces `Path` entries that point to directories with the media files that they contain.
+///
+/// Files are selected by their extension, compared case-insensitively, and listed in natural
+/// order so that `Track 2.mp3` precedes `Track 10.mp3`. By default subdirectories are expanded
+/// in place and hidden files and directories, i.e. those whose names begin with `.`, are skipped.
+///
+/// Relative paths are interpreted relative to the current directory, so the entries of a
+/// playlist should first be resolved against its location, e.g. via `ReadOptions::base`.
+///
+/// ```no_run
+/// extern crate m3u;
+///
+/// fn main() {
+///     let expander = m3u::Expander::new().extensions(&["flac", "mp3"]).recursive(false);
+///     let entries = vec![m3u::path_entry("/music/Some Album"), m3u::path_entry("/music/a.mp3")];
+///     for entry in expander.expand_entries(entries).unwrap() {
+///         println!("{:?}", entry);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expander {
+    /// The lower case extensions of the files to select, or all files if empty.
+    extensions: Vec<String>,
+    /// Whether or not subdirectories are expanded.
+    recursive: bool,
+    /// Whether or not hidden files and directories are included.
+    include_hidden: bool,
+}
+
+
+impl Expander {
+
+    /// Create an expander selecting files with the `DEFAULT_MEDIA_EXTENSIONS` recursively,
+    /// skipping hidden files.
+    pub fn new() -> Self {
+        Expander {
+            extensions: DEFAULT_MEDIA_EXTENSIONS.iter().map(|ext| ext.to_string()).collect(),
+            recursive: true,
+            include_hidden: false,
+        }
+    }
+
+    /// Select files with the given extensions, e.g. `&["mp3", "ogg"]`. All files are selected if
+    /// no extensions are given.
+    pub fn extensions<I>(mut self, extensions: I) -> Self
+        where I: IntoIterator,
+              I::Item: AsRef<str>,
+    {
+        self.extensions = extensions.into_iter()
+            .map(|ext| ext.as_ref().trim_start_matches('.').to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Whether or not the files within subdirectories are included.
+    pub fn recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Whether or not hidden files and directories are included.
+    pub fn include_hidden(mut self, include_hidden: bool) -> Self {
+        self.include_hidden = include_hidden;
+        self
+    }
+
+    /// Whether or not the file at the given path is selected by its extension.
+    pub fn selects(&self, path: &Path) -> bool {
+        if self.extensions.is_empty() {
+            return true;
+        }
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) => self.extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)),
+            None => false,
+        }
+    }
+
+    /// The selected files within the given directory, in natural order.
+    ///
+    /// Directories that are reached more than once via symbolic links are only expanded once.
+    /// Entries within the directory that cannot be read, such as dangling symbolic links or
+    /// subdirectories without permission, are skipped. Only a failure to read `dir` itself is
+    /// returned as an error.
+    pub fn expand_dir<P>(&self, dir: P) -> Result<Vec<PathBuf>, std::io::Error>
+        where P: AsRef<Path>,
+    {
+        let mut files = Vec::new();
+        let mut visited = HashSet::new();
+        self.expand_dir_into(dir.as_ref(), &mut visited, &mut files)?;
+        Ok(files)
+    }
+
+    /// Replace each `Path` entry pointing to a directory with an entry for each of the selected
+    /// files within it. All other entries are kept as they are.
+    pub fn expand_entries<I>(&self, entries: I) -> Result<Vec<Entry>, std::io::Error>
+        where I: IntoIterator<Item = Entry>,
+    {
+        let mut expanded = Vec::new();
+        for entry in entries {
+            match self.expand_entry(&entry)? {
+                Some(files) => expanded.extend(files.into_iter().map(Entry::Path)),
+                None => expanded.push(entry),
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// The same as `expand_entries`, but for extended entries.
+    ///
+    /// The files of an expanded directory have an `Unknown` duration and an empty name, as the
+    /// information of the directory's entry does not describe them.
+    pub fn expand_entry_exts<I>(&self, entry_exts: I) -> Result<Vec<EntryExt>, std::io::Error>
+        where I: IntoIterator<Item = EntryExt>,
+    {
+        let mut expanded = Vec::new();
+        for entry_ext in entry_exts {
+            match self.expand_entry(&entry_ext.entry)? {
+                Some(files) => expanded.extend(files.into_iter().map(|file| {
+                    Entry::Path(file).extend(Duration::Unknown, "")
+                })),
+                None => expanded.push(entry_ext),
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// The subdirectories of the given directory in natural order, skipping hidden directories
+    /// unless they are included.
+    pub(crate) fn subdirectories(&self, dir: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+        let mut subdirectories = Vec::new();
+        for (_, path) in self.children(dir)? {
+            if path.is_dir() {
+                subdirectories.push(path);
+            }
+        }
+        Ok(subdirectories)
+    }
+
+    /// The files of the given entry if it is a `Path` pointing to a directory.
+    fn expand_entry(&self, entry: &Entry) -> Result<Option<Vec<PathBuf>>, std::io::Error> {
+        match *entry {
+            Entry::Path(ref path) if path.is_dir() => self.expand_dir(path).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// The names and paths of the entries within the given directory in natural order, skipping
+    /// hidden entries unless they are included.
+    fn children(&self, dir: &Path) -> Result<Vec<(String, PathBuf)>, std::io::Error> {
+        let mut children = Vec::new();
+        for child in std::fs::read_dir(dir)? {
+            let child = match child {
+                Ok(child) => child,
+                Err(_) => continue,
+            };
+            let name = child.file_name().to_string_lossy().into_owned();
+            if !self.include_hidden && name.starts_with('.') {
+                continue;
+            }
+            children.push((name, child.path()));
+        }
+        children.sort_by(|a, b| natural_cmp(&a.0, &b.0));
+        Ok(children)
+    }
+
+    /// Push the selected files within the given directory onto `files`.
+    fn expand_dir_into(
+        &self,
+        dir: &Path,
+        visited: &mut HashSet<PathBuf>,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<(), std::io::Error>
+    {
+        if !visited.insert(std::fs::canonicalize(dir)?) {
+            return Ok(());
+        }
+        for (_, path) in self.children(dir)? {
+            // Follows symbolic links so that linked files and directories are included, skipping
+            // those that are dangling.
+            let metadata = match std::fs::metadata(&path) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            if metadata.is_dir() {
+                if self.recursive {
+                    // Subdirectories that cannot be read are skipped.
+                    self.expand_dir_into(&path, visited, files).ok();
+                }
+            } else if self.selects(&path) {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+}
+
+
+/// Compare the given names in natural order: case-insensitively, with runs of digits compared by
+/// their numeric value so that `Track 2` precedes `Track 10`.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        let (a_char, b_char) = match (a_chars.peek(), b_chars.peek()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(&a_char), Some(&b_char)) => (a_char, b_char),
+        };
+        let ordering = if a_char.is_ascii_digit() && b_char.is_ascii_digit() {
+            let a_digits = take_digits(&mut a_chars);
+            let b_digits = take_digits(&mut b_chars);
+            let a_trimmed = a_digits.trim_start_matches('0');
+            let b_trimmed = b_digits.trim_start_matches('0');
+            a_trimmed.len().cmp(&b_trimmed.len()).then_with(|| a_trimmed.cmp(b_trimmed))
+        } else {
+            a_chars.next();
+            b_chars.next();
+            a_char.to_lowercase().cmp(b_char.to_lowercase())
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Take the run of ASCII digits at the front of the given characters.
+fn take_digits<I>(chars: &mut std::iter::Peekable<I>) -> String
+    where I: Iterator<Item = char>,
+{
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+    digits
+}
+
+
+impl Default for Expander {
+    fn default() -> Self {
+        Self::new()
+    }
+}
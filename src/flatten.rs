@@ -0,0 +1,293 @@
+use {Base, Entry, EntryExt, OpenError};
+use detect;
+use std;
+use std::path::Path;
+use url::Url;
+
+/// The depth to which nested playlists are followed by default.
+pub const DEFAULT_MAX_DEPTH: usize = 16;
+
+/// Retrieves the content of playlists referred to by `Url` entries.
+///
+/// Implemented for all closures of the form `Fn(&Url) -> Result<Vec<u8>, std::io::Error>`, so
+/// that any HTTP client may be plugged in.
+pub trait Fetcher {
+    /// Fetch the raw content at the given URL.
+    fn fetch(&self, url: &Url) -> Result<Vec<u8>, std::io::Error>;
+}
+
+/// Follows entries that point to nested **M3U** and **PLS** playlists, producing a flattened
+/// sequence of their media entries.
+///
+/// An entry is considered a nested playlist if its file extension is `m3u`, `m3u8` or `pls`.
+/// `Path` entries are always followed while `Url` entries are only followed if a `Fetcher` has
+/// been given. The entries of each nested playlist are resolved against its own location.
+///
+/// ```no_run
+/// extern crate m3u;
+///
+/// fn main() {
+///     for entry in m3u::Flattener::new().max_depth(4).open("party.m3u") {
+///         match entry {
+///             Ok(flat) => println!("{:?} via {:?}", flat.entry_ext.entry, flat.provenance),
+///             Err(err) => eprintln!("skipping {:?}: {}", err.playlist, err),
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct Flattener<'a> {
+    /// The maximum depth of nesting below the root playlist.
+    max_depth: usize,
+    /// Used to retrieve the content of nested playlists referred to by `Url` entries.
+    fetcher: Option<&'a dyn Fetcher>,
+}
+
+/// An iterator yielding the flattened entries of a playlist and its nested playlists.
+///
+/// Nested playlists that cannot be followed yield an error, after which iteration continues with
+/// the entry following the nested playlist.
+pub struct Flatten<'a> {
+    /// The options with which the playlists are followed.
+    flattener: Flattener<'a>,
+    /// A nested playlist that is yet to be opened.
+    pending: Option<Entry>,
+    /// The playlists that are currently being flattened, beginning with the root playlist.
+    stack: Vec<Frame>,
+}
+
+/// An entry produced by `Flatten`, alongside the playlists through which it was reached.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlatEntry {
+    /// The entry, resolved against the location of the playlist containing it.
+    pub entry_ext: EntryExt,
+    /// The locations of the playlists through which the entry was reached, beginning with the
+    /// root playlist and ending with the playlist containing the entry.
+    pub provenance: Vec<Entry>,
+}
+
+/// A nested playlist that could not be followed.
+#[derive(Debug)]
+pub struct FlattenError {
+    /// The location of the playlist that could not be followed.
+    pub playlist: Entry,
+    /// The locations of the playlists through which `playlist` was reached, beginning with the
+    /// root playlist. Empty if `playlist` is the root playlist.
+    pub provenance: Vec<Entry>,
+    /// The reason for which the playlist could not be followed.
+    pub kind: FlattenErrorKind,
+}
+
+/// The reasons for which a nested playlist may not be followed.
+#[derive(Debug)]
+pub enum FlattenErrorKind {
+    /// The playlist could not be opened, fetched or read.
+    Open(OpenError),
+    /// The playlist is one of the playlists through which it was reached.
+    Cycle,
+    /// Following the playlist would exceed the maximum depth.
+    DepthExceeded,
+}
+
+/// A playlist that is currently being flattened.
+struct Frame {
+    /// The location of the playlist.
+    location: Entry,
+    /// The location used to detect cycles, e.g. the canonical path of the playlist.
+    identity: Entry,
+    /// The remaining entries of the playlist.
+    entries: std::vec::IntoIter<EntryExt>,
+}
+
+
+impl<'a> Flattener<'a> {
+
+    /// Create a flattener that follows `Path` entries up to `DEFAULT_MAX_DEPTH` levels deep.
+    pub fn new() -> Self {
+        Flattener { max_depth: DEFAULT_MAX_DEPTH, fetcher: None }
+    }
+
+    /// The maximum depth of nesting below the root playlist. With a depth of `0`, every nested
+    /// playlist within the root playlist yields a `DepthExceeded` error.
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Follow `Url` entries, retrieving their content via the given fetcher.
+    pub fn fetcher(mut self, fetcher: &'a dyn Fetcher) -> Self {
+        self.fetcher = Some(fetcher);
+        self
+    }
+
+    /// Flatten the playlist stored at the given path.
+    pub fn open<P>(self, path: P) -> Flatten<'a>
+        where P: AsRef<Path>,
+    {
+        self.flatten(Entry::Path(path.as_ref().to_path_buf()))
+    }
+
+    /// Flatten the playlist at the given location, which is either a `Path` or, if a `Fetcher`
+    /// has been given, a `Url`.
+    pub fn flatten(self, playlist: Entry) -> Flatten<'a> {
+        Flatten { flattener: self, pending: Some(playlist), stack: Vec::new() }
+    }
+
+    /// Whether or not the given entry refers to a nested playlist that should be followed.
+    fn follows(&self, entry: &Entry) -> bool {
+        let path = match *entry {
+            Entry::Path(ref path) => path.to_string_lossy().into_owned(),
+            Entry::Url(ref url) if self.fetcher.is_some() => url.path().to_string(),
+            Entry::Url(_) | Entry::Uri(_) => return false,
+        };
+        let extension = match path.rfind('.') {
+            Some(i) => path[i + 1..].to_lowercase(),
+            None => return false,
+        };
+        extension == "m3u" || extension == "m3u8" || extension == "pls"
+    }
+
+    /// Read the entries of the playlist at the given location, resolved against that location.
+    fn read(&self, location: &Entry) -> Result<Vec<EntryExt>, OpenError> {
+        let (playlist, base) = match *location {
+            Entry::Path(ref path) => (detect::open_any(path)?, Base::from_playlist_path(path)),
+            Entry::Url(ref url) => {
+                let fetcher = self.fetcher.ok_or_else(|| unsupported("no `Fetcher` was given"))?;
+                let bytes = fetcher.fetch(url)?;
+                (detect::read_any(&bytes[..])?, Base::from_playlist_url(url.clone()))
+            },
+            Entry::Uri(_) => return Err(unsupported("a `Uri` does not locate a playlist").into()),
+        };
+        let entries = playlist.entries
+            .into_iter()
+            .map(|mut entry_ext| {
+                entry_ext.entry = entry_ext.entry.resolve(&base);
+                entry_ext
+            })
+            .collect();
+        Ok(entries)
+    }
+
+}
+
+impl<'a> Flatten<'a> {
+
+    /// The locations of the playlists that are currently being flattened.
+    fn provenance(&self) -> Vec<Entry> {
+        self.stack.iter().map(|frame| frame.location.clone()).collect()
+    }
+
+    /// Open the given nested playlist and begin flattening its entries, returning the reason for
+    /// which it could not be followed, if any.
+    fn push(&mut self, location: Entry) -> Option<FlattenError> {
+        let kind = if self.stack.len() > self.flattener.max_depth {
+            FlattenErrorKind::DepthExceeded
+        } else {
+            let identity = identity(&location);
+            if self.stack.iter().any(|frame| frame.identity == identity) {
+                FlattenErrorKind::Cycle
+            } else {
+                match self.flattener.read(&location) {
+                    Ok(entries) => {
+                        let entries = entries.into_iter();
+                        self.stack.push(Frame { location, identity, entries });
+                        return None;
+                    },
+                    Err(err) => FlattenErrorKind::Open(err),
+                }
+            }
+        };
+        Some(FlattenError { playlist: location, provenance: self.provenance(), kind })
+    }
+
+}
+
+
+/// An error describing a playlist location that cannot be read.
+fn unsupported(description: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Unsupported, description)
+}
+
+/// The location used to detect whether two playlists are the same.
+///
+/// Paths are canonicalized where possible so that different routes to the same file are
+/// recognized.
+fn identity(location: &Entry) -> Entry {
+    match *location {
+        Entry::Path(ref path) => match std::fs::canonicalize(path) {
+            Ok(path) => Entry::Path(path),
+            Err(_) => location.clone(),
+        },
+        Entry::Url(_) | Entry::Uri(_) => location.clone(),
+    }
+}
+
+
+impl<F> Fetcher for F
+    where F: Fn(&Url) -> Result<Vec<u8>, std::io::Error>,
+{
+    fn fetch(&self, url: &Url) -> Result<Vec<u8>, std::io::Error> {
+        (*self)(url)
+    }
+}
+
+impl<'a> Default for Flattener<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> std::fmt::Debug for Flattener<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("Flattener")
+            .field("max_depth", &self.max_depth)
+            .field("fetcher", &self.fetcher.is_some())
+            .finish()
+    }
+}
+
+impl<'a> Iterator for Flatten<'a> {
+    type Item = Result<FlatEntry, FlattenError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(location) = self.pending.take() {
+                if let Some(err) = self.push(location) {
+                    return Some(Err(err));
+                }
+            }
+            let entry_ext = match self.stack.last_mut()?.entries.next() {
+                Some(entry_ext) => entry_ext,
+                None => {
+                    self.stack.pop();
+                    continue;
+                },
+            };
+            if self.flattener.follows(&entry_ext.entry) {
+                self.pending = Some(entry_ext.entry);
+                continue;
+            }
+            return Some(Ok(FlatEntry { entry_ext, provenance: self.provenance() }));
+        }
+    }
+}
+
+impl std::error::Error for FlattenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self.kind {
+            FlattenErrorKind::Open(ref err) => Some(err),
+            FlattenErrorKind::Cycle | FlattenErrorKind::DepthExceeded => None,
+        }
+    }
+}
+
+impl std::fmt::Display for FlattenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match self.kind {
+            FlattenErrorKind::Open(ref err) => err.fmt(f),
+            FlattenErrorKind::Cycle =>
+                write!(f, "the playlist is nested within itself"),
+            FlattenErrorKind::DepthExceeded =>
+                write!(f, "the playlist is nested beyond the maximum depth"),
+        }
+    }
+}
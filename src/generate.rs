@@ -0,0 +1,207 @@
+use {Duration, Entry, EscapePolicy, Expander, PathMode, WriteOptions, Writer};
+use std;
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Generates **M3U** playlists from the media files found within a directory tree.
+///
+/// Media files are selected and ordered by an `Expander`. Playlists are written with paths
+/// relative to their own location, so that the tree may be moved as a whole.
+///
+/// ```no_run
+/// extern crate m3u;
+///
+/// fn main() {
+///     let generator = m3u::Generator::new().grouping(m3u::Grouping::PerDirectory);
+///     for playlist in generator.generate("/music").unwrap() {
+///         playlist.save().unwrap();
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Generator {
+    /// Selects and orders the media files within each directory.
+    expander: Expander,
+    /// How the media files are grouped into playlists.
+    grouping: Grouping,
+    /// Whether or not extended playlists are generated.
+    extended: bool,
+}
+
+/// How a `Generator` groups media files into playlists.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Grouping {
+    /// One playlist for each directory containing media files, listing the files directly within
+    /// the directory and stored within it, e.g. `Some Album/Some Album.m3u`.
+    PerDirectory,
+    /// A single playlist listing the media files of the whole tree, stored at its root.
+    Single,
+}
+
+/// A playlist produced by a `Generator`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GeneratedPlaylist {
+    /// The path at which the playlist is saved. Entries are written relative to its directory,
+    /// so it may be changed before the playlist is written.
+    pub path: PathBuf,
+    /// The absolute paths of the media files in order.
+    pub files: Vec<PathBuf>,
+    /// Whether or not the playlist is written in the extended format.
+    pub extended: bool,
+}
+
+
+impl Generator {
+
+    /// Create a generator producing one plain playlist per directory from the media files
+    /// selected by `Expander::new()`.
+    pub fn new() -> Self {
+        Generator { expander: Expander::new(), grouping: Grouping::PerDirectory, extended: false }
+    }
+
+    /// Select and order media files with the given `Expander`.
+    ///
+    /// Whether or not the expander is recursive is determined by the `Grouping`.
+    pub fn expander(mut self, expander: Expander) -> Self {
+        self.expander = expander;
+        self
+    }
+
+    /// How the media files are grouped into playlists.
+    pub fn grouping(mut self, grouping: Grouping) -> Self {
+        self.grouping = grouping;
+        self
+    }
+
+    /// Whether or not extended playlists are generated, naming each entry after its file stem.
+    pub fn extended(mut self, extended: bool) -> Self {
+        self.extended = extended;
+        self
+    }
+
+    /// Walk the directory tree at `root`, producing the playlists without writing them.
+    ///
+    /// Directories without any selected media files produce no playlist, and subdirectories that
+    /// cannot be read are skipped.
+    pub fn generate<P>(&self, root: P) -> Result<Vec<GeneratedPlaylist>, std::io::Error>
+        where P: AsRef<Path>,
+    {
+        let root = std::env::current_dir()?.join(root);
+        let playlist = |dir: &Path, files| GeneratedPlaylist {
+            path: dir.join(format!("{}.m3u", playlist_name(dir))),
+            files,
+            extended: self.extended,
+        };
+        let mut playlists = Vec::new();
+        match self.grouping {
+            Grouping::Single => {
+                let files = self.expander.clone().recursive(true).expand_dir(&root)?;
+                if !files.is_empty() {
+                    playlists.push(playlist(&root, files));
+                }
+            },
+            Grouping::PerDirectory => {
+                let expander = self.expander.clone().recursive(false);
+                let mut dirs = vec![root.clone()];
+                let mut visited = HashSet::new();
+                while let Some(dir) = dirs.pop() {
+                    let listed = std::fs::canonicalize(&dir).and_then(|canonical| {
+                        if !visited.insert(canonical) {
+                            return Ok(None);
+                        }
+                        Ok(Some((expander.expand_dir(&dir)?, expander.subdirectories(&dir)?)))
+                    });
+                    let (files, subdirectories) = match listed {
+                        Ok(Some(listed)) => listed,
+                        Ok(None) => continue,
+                        // Subdirectories that cannot be read are skipped, as by the `Expander`.
+                        Err(_) if dir != root => continue,
+                        Err(err) => return Err(err),
+                    };
+                    if !files.is_empty() {
+                        playlists.push(playlist(&dir, files));
+                    }
+                    // Pushed in reverse so that subdirectories are visited in natural order.
+                    dirs.extend(subdirectories.into_iter().rev());
+                }
+            },
+        }
+        Ok(playlists)
+    }
+
+    /// Generate the playlists of the tree at `root` and save each of them, returning the paths
+    /// of the saved playlists.
+    pub fn generate_files<P>(&self, root: P) -> Result<Vec<PathBuf>, std::io::Error>
+        where P: AsRef<Path>,
+    {
+        let mut paths = Vec::new();
+        for playlist in self.generate(root)? {
+            playlist.save()?;
+            paths.push(playlist.path);
+        }
+        Ok(paths)
+    }
+
+}
+
+impl GeneratedPlaylist {
+
+    /// Write the playlist to the given writer with paths relative to the directory of `path`.
+    ///
+    /// Names and paths are written with `EscapePolicy::Sanitize`, so that any file name may be
+    /// written, e.g. a path beginning with `#` is preceded by `./`.
+    pub fn write<W>(&self, writer: W) -> Result<(), std::io::Error>
+        where W: Write,
+    {
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("")).to_path_buf();
+        let options = WriteOptions {
+            paths: PathMode::Relative(dir),
+            escape: EscapePolicy::Sanitize,
+            ..Default::default()
+        };
+        if self.extended {
+            let mut writer = Writer::new_ext_with_options(writer, options)?;
+            for file in &self.files {
+                let name = file.file_stem().map(|stem| stem.to_string_lossy().into_owned());
+                let name = name.unwrap_or_default();
+                let entry_ext = Entry::Path(file.clone()).extend(Duration::Unknown, name);
+                writer.write_entry(&entry_ext)?;
+            }
+            writer.flush()
+        } else {
+            let mut writer = Writer::new_with_options(writer, options);
+            for file in &self.files {
+                writer.write_entry(&Entry::Path(file.clone()))?;
+            }
+            writer.flush()
+        }
+    }
+
+    /// Write the playlist to the file at `path`, replacing any existing file.
+    ///
+    /// The playlist is rendered before the file is created, so that no partially written file is
+    /// left behind if writing fails.
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)?;
+        std::fs::write(&self.path, bytes)
+    }
+
+}
+
+
+/// The name of the playlist generated for the given directory.
+fn playlist_name(dir: &Path) -> String {
+    match dir.file_name() {
+        Some(name) => name.to_string_lossy().into_owned(),
+        None => "playlist".to_string(),
+    }
+}
+
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,354 @@
+//! Streaming conversion between `EntryExt`s and JSON.
+//!
+//! Two layouts are supported:
+//!
+//! - A single JSON array of entries, read and written by `Reader` and `Writer`.
+//! - [JSON Lines](http://jsonlines.org/), one entry per line, read and written by `LinesReader`
+//!   and `LinesWriter`.
+//!
+//! Each entry uses the `serde` representation documented at the crate root, e.g.
+//!
+//! ```json
+//! {"entry":{"path":"Sample.mp3"},"extinf":{"duration_secs":123.0,"name":"Sample title"}}
+//! ```
+
+use EntryExt;
+use serde::Deserialize;
+use serde_json;
+use std;
+use std::io::{BufRead, Write};
+
+/// A reader that lazily reads `EntryExt`s from a JSON array.
+///
+/// Only a single entry is held in memory at a time.
+pub struct Reader<R>
+    where R: BufRead,
+{
+    /// The reader from which the JSON array is read.
+    reader: R,
+    /// Whether or not the closing bracket of the array has been read.
+    finished: bool,
+    /// Whether or not at least one element has been read.
+    started: bool,
+}
+
+/// A writer that writes `EntryExt`s as elements of a single JSON array.
+///
+/// The opening bracket is written upon construction and the closing bracket is written by
+/// `finish`. If `finish` is not called, the closing bracket is written when the writer is dropped,
+/// though any errors that occur in the process cannot be handled.
+pub struct Writer<W>
+    where W: Write,
+{
+    /// The writer to which the JSON array is written.
+    writer: W,
+    /// The number of entries written so far.
+    count: usize,
+    /// Whether or not the closing bracket has been written.
+    finished: bool,
+}
+
+/// A reader that lazily reads `EntryExt`s from JSON Lines, one entry per line.
+///
+/// Empty lines are skipped.
+pub struct LinesReader<R>
+    where R: BufRead,
+{
+    /// The reader from which the lines are read.
+    reader: R,
+    /// String used for buffering read lines.
+    line_buffer: String,
+}
+
+/// A writer that writes `EntryExt`s as JSON Lines, one entry per line.
+pub struct LinesWriter<W>
+    where W: Write,
+{
+    /// The writer to which the lines are written.
+    writer: W,
+}
+
+/// An iterator that yields the `EntryExt`s of a JSON array.
+pub struct EntryExts<'r, R>
+    where R: 'r + BufRead,
+{
+    reader: &'r mut Reader<R>,
+}
+
+/// An iterator that yields the `EntryExt`s of a JSON Lines document.
+pub struct LinesEntryExts<'r, R>
+    where R: 'r + BufRead,
+{
+    reader: &'r mut LinesReader<R>,
+}
+
+/// Errors that may occur while reading or writing JSON.
+#[derive(Debug)]
+pub enum Error {
+    /// Errors produced by the inner reader or writer.
+    Io(std::io::Error),
+    /// The JSON for an entry was malformed or did not match the expected representation.
+    Json(serde_json::Error),
+    /// An unexpected byte was found between the elements of a JSON array.
+    ///
+    /// `None` indicates that the input ended unexpectedly.
+    Unexpected {
+        /// The byte that was found.
+        found: Option<u8>,
+        /// A description of what was expected instead.
+        expected: &'static str,
+    },
+}
+
+
+impl<R> Reader<R>
+    where R: BufRead,
+{
+
+    /// Create a reader that reads the elements of a JSON array from the given reader.
+    ///
+    /// The opening bracket of the array is read immediately.
+    pub fn new(mut reader: R) -> Result<Self, Error> {
+        match next_non_whitespace(&mut reader)? {
+            Some(b'[') => reader.consume(1),
+            found => return Err(Error::Unexpected { found, expected: "`[`" }),
+        }
+        Ok(Reader { reader, finished: false, started: false })
+    }
+
+    /// Attempt to read the next `EntryExt` from the array.
+    ///
+    /// Returns `Ok(None)` once the closing bracket has been read.
+    fn read_next_entry(&mut self) -> Result<Option<EntryExt>, Error> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        // Expect either the end of the array or a separator between elements.
+        match next_non_whitespace(&mut self.reader)? {
+            Some(b']') => {
+                self.reader.consume(1);
+                self.finished = true;
+                return Ok(None);
+            },
+            Some(b',') if self.started => self.reader.consume(1),
+            Some(_) if !self.started => (),
+            found => return Err(Error::Unexpected { found, expected: "`,` or `]`" }),
+        }
+
+        self.started = true;
+        let mut deserializer = serde_json::Deserializer::from_reader(&mut self.reader);
+        let entry = EntryExt::deserialize(&mut deserializer)?;
+        Ok(Some(entry))
+    }
+
+    /// Produce an iterator that yields `EntryExt`s.
+    ///
+    /// All `EntryExt`s are lazily read from the inner buffered reader.
+    pub fn entry_exts(&mut self) -> EntryExts<'_, R> {
+        EntryExts { reader: self }
+    }
+
+    /// Produce the inner `reader`.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+}
+
+impl<W> Writer<W>
+    where W: Write,
+{
+
+    /// Create a writer that writes a JSON array of entries.
+    ///
+    /// The opening bracket of the array is written immediately.
+    pub fn new(mut writer: W) -> Result<Self, std::io::Error> {
+        writer.write_all(b"[")?;
+        Ok(Writer { writer, count: 0, finished: false })
+    }
+
+    /// Attempt to write the given `EntryExt` as the next element of the array.
+    pub fn write_entry(&mut self, entry_ext: &EntryExt) -> Result<(), Error> {
+        if self.count > 0 {
+            self.writer.write_all(b",")?;
+        }
+        self.writer.write_all(b"\n")?;
+        serde_json::to_writer(&mut self.writer, entry_ext)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Write the closing bracket of the array and flush the inner writer.
+    pub fn finish(mut self) -> Result<(), std::io::Error> {
+        self.finished = true;
+        self.writer.write_all(b"\n]\n")?;
+        self.writer.flush()
+    }
+
+}
+
+impl<R> LinesReader<R>
+    where R: BufRead,
+{
+
+    /// Create a reader that reads JSON Lines from the given reader.
+    pub fn new(reader: R) -> Self {
+        LinesReader { reader, line_buffer: String::new() }
+    }
+
+    /// Attempt to read the next `EntryExt`, skipping empty lines.
+    ///
+    /// Returns `Ok(None)` when there are no more lines.
+    fn read_next_entry(&mut self) -> Result<Option<EntryExt>, Error> {
+        loop {
+            self.line_buffer.clear();
+            if self.reader.read_line(&mut self.line_buffer)? == 0 {
+                return Ok(None);
+            }
+            let line = self.line_buffer.trim();
+            if line.is_empty() {
+                continue;
+            }
+            return Ok(Some(serde_json::from_str(line)?));
+        }
+    }
+
+    /// Produce an iterator that yields `EntryExt`s.
+    ///
+    /// All `EntryExt`s are lazily read from the inner buffered reader.
+    pub fn entry_exts(&mut self) -> LinesEntryExts<'_, R> {
+        LinesEntryExts { reader: self }
+    }
+
+    /// Produce the inner `reader`.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+}
+
+impl<W> LinesWriter<W>
+    where W: Write,
+{
+
+    /// Create a writer that writes JSON Lines to the given writer.
+    pub fn new(writer: W) -> Self {
+        LinesWriter { writer }
+    }
+
+    /// Attempt to write the given `EntryExt` as a single line.
+    pub fn write_entry(&mut self, entry_ext: &EntryExt) -> Result<(), Error> {
+        serde_json::to_writer(&mut self.writer, entry_ext)?;
+        self.writer.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// `Flush` the `writer` output stream, ensuring that all intermediately buffered entries reach
+    /// their destination.
+    pub fn flush(mut self) -> Result<(), std::io::Error> {
+        self.writer.flush()
+    }
+
+}
+
+
+/// Skip whitespace and return the next byte without consuming it.
+fn next_non_whitespace<R>(reader: &mut R) -> Result<Option<u8>, std::io::Error>
+    where R: BufRead,
+{
+    loop {
+        let (skip, found) = {
+            let buf = reader.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(None);
+            }
+            match buf.iter().position(|b| !b.is_ascii_whitespace()) {
+                Some(i) => (i, Some(buf[i])),
+                None => (buf.len(), None),
+            }
+        };
+        reader.consume(skip);
+        if found.is_some() {
+            return Ok(found);
+        }
+    }
+}
+
+
+impl<'r, R> Iterator for EntryExts<'r, R>
+    where R: BufRead,
+{
+    type Item = Result<EntryExt, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.read_next_entry() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => None,
+            Err(err) => {
+                self.reader.finished = true;
+                Some(Err(err))
+            },
+        }
+    }
+}
+
+impl<'r, R> Iterator for LinesEntryExts<'r, R>
+    where R: BufRead,
+{
+    type Item = Result<EntryExt, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.reader.read_next_entry() {
+            Ok(Some(entry)) => Some(Ok(entry)),
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+
+impl<W> Drop for Writer<W>
+    where W: Write,
+{
+    fn drop(&mut self) {
+        if !self.finished {
+            self.writer.write_all(b"\n]\n").ok();
+            self.writer.flush().ok();
+        }
+    }
+}
+
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Json(ref err) => Some(err),
+            Error::Unexpected { .. } => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::Io(ref err) => err.fmt(f),
+            Error::Json(ref err) => err.fmt(f),
+            Error::Unexpected { found: Some(byte), expected } =>
+                write!(f, "expected {} but found `{}`", expected, byte as char),
+            Error::Unexpected { found: None, expected } =>
+                write!(f, "expected {} but reached the end of the input", expected),
+        }
+    }
+}
@@ -4,56 +4,159 @@
 //!
 //! There is no formal specification for the **M3U** format. This crate is implemented based on the
 //! rough description under the format's current wikipedia entry.
+//!
+//! The **PLS**, **XSPF**, **ASX**, **WPL** and **CUE** formats are supported by their respective
+//! modules, where the XML based **XSPF**, **ASX** and **WPL** formats require the `xml` feature.
+//! `open_any` detects the format of a playlist from its content and reads it into a common
+//! `Playlist` regardless of the format.
+//!
+//! When the `tags` feature is enabled, the `tags` module fills `ExtInf`s from the tags and
+//! durations of local media files.
+//!
+//! ## Serde
+//!
+//! When the `serde` feature is enabled, `Entry`, `EntryExt` and `ExtInf` implement `Serialize`
+//! and `Deserialize` with the following stable representation (shown here as JSON):
+//!
+//! - An `Entry` is an object with a single `"path"`, `"url"` or `"uri"` key whose value is the
+//!   path, URL or URI as a string, e.g. `{"path": "Greatest Hits\\Example.ogg"}`. Paths that
+//!   are not valid UTF-8 fail to serialize.
+//! - An `ExtInf` is an object with a `"duration_secs"` number, a `"name"` string and an
+//!   `"attributes"` object mapping attribute keys to values in order of appearance. The
+//!   `"attributes"` key is omitted when there are no attributes and is optional when
+//!   deserializing. A `Duration::Known` duration is written as its number of seconds,
+//!   `Duration::Live` as `-1` and `Duration::Unknown` as `null`.
+//! - An `EntryExt` is an object with an `"entry"` and an `"extinf"` key.
 
 #![warn(missing_docs)]
 
+#[cfg(feature = "csv")]
+extern crate csv as csv_crate;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde;
+#[cfg(feature = "json")]
+extern crate serde_json;
+#[cfg(feature = "tags")]
+extern crate symphonia;
 pub extern crate url;
+#[cfg(feature = "xml")]
+extern crate xml;
 
+#[cfg(feature = "xml")]
+pub mod asx;
+#[cfg(feature = "csv")]
+pub mod csv;
+pub mod cue;
+mod detect;
+mod duration;
+mod edit;
+mod expand;
+mod flatten;
+mod generate;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "xml")]
+mod markup;
+mod name;
+pub mod pls;
+mod portable_path;
 mod read;
+mod remap;
+mod resolve;
+#[cfg(feature = "serde")]
+mod serde_impls;
+#[cfg(feature = "tags")]
+pub mod tags;
+mod validate;
+#[cfg(feature = "xml")]
+pub mod wpl;
 mod write;
+#[cfg(feature = "xml")]
+pub mod xspf;
 
-pub use read::{Reader, EntryReader, EntryExtReader, Entries, EntryExts,
-               EntryExtReaderConstructionError, ReadEntryExtError};
-pub use write::{Writer, EntryWriter, EntryExtWriter};
+pub use duration::Duration;
+pub use name::{TrackName, NameParser, NameTemplate, DEFAULT_NAME_TEMPLATE};
+pub use portable_path::{PortablePath, PathPrefix, DriveMapping};
+pub use read::{Reader, EntryReader, EntryExtReader, Entries, EntryExts, ReadOptions,
+               EntryExtReaderConstructionError, ReadEntryExtError, URI_SCHEMES};
+pub use detect::{Format, Playlist, OpenError, detect_format, read_any, open_any};
+pub use edit::{Editor, Edit, Change, EditError};
+pub use expand::{Expander, DEFAULT_MEDIA_EXTENSIONS};
+pub use generate::{Generator, Grouping, GeneratedPlaylist};
+pub use flatten::{Flattener, Flatten, FlatEntry, FlattenError, FlattenErrorKind, Fetcher,
+                  DEFAULT_MAX_DEPTH};
+pub use remap::{Remapper, RemapReport};
+pub use resolve::Base;
+pub use validate::{ValidationReport, PathProblem, PathProblemKind, validate_paths};
+#[cfg(feature = "parallel")]
+pub use validate::validate_paths_parallel;
+pub use write::{Writer, EntryWriter, EntryExtWriter, WriteOptions, PathMode, Separator,
+                DurationFormat, EscapePolicy, UnsafeValueError, UnsafeField, LineEnding};
 pub use url::Url;
 
+/// The `ExtInf` attribute holding the creator or performer of an entry, to which the **XSPF**
+/// `<creator>`, **ASX** `<author>` and **CUE** `PERFORMER` map.
+pub const CREATOR_ATTRIBUTE: &str = "creator";
+
 /// An entry in an **M3U** multimedia playlist.
 ///
 /// Describes the source of the media.
 ///
-/// In rare cases an `Entry` may point to another `.m3u` file. If a user wishes to support this in
-/// their application, they must be sure to handle cycles within the **M3U** graph.
-#[derive(Clone, Debug, Hash, PartialEq)]
+/// In rare cases an `Entry` may point to another `.m3u` file. Such nested playlists may be
+/// followed via a `Flattener`, which takes care of cycles within the **M3U** graph.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum Entry {
     /// The entry resides at the given `Path`.
     ///
     /// The `Path` may be either absolute or relative.
     ///
     /// Note that the `Path` may also point to a directory. After starting, the media player would
-    /// play all contents of the directory.
+    /// play all contents of the directory. Such entries may be expanded into the media files
+    /// within the directory via an `Expander`.
     Path(std::path::PathBuf),
     /// The entry can be found at the given `Url`.
-    Url(url::Url),
+    Url(#[cfg_attr(feature = "serde", serde(with = "serde_impls::url"))] url::Url),
+    /// The entry is identified by an opaque URI without a host, e.g. `spotify:track:...`.
+    ///
+    /// A line is only read as a `Uri` if its scheme is one of `URI_SCHEMES`, so that relative
+    /// paths such as `Beatles: Help.mp3` or `track01:intro.mp3` remain `Path`s. `file:` URLs are
+    /// read as `Path`s.
+    Uri(#[cfg_attr(feature = "serde", serde(with = "serde_impls::url"))] url::Url),
 }
 
 /// An entry with some associated extra information.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct EntryExt {
-    /// The M3U entry. Can be either a `Path` or `Url`.
+    /// The M3U entry. Can be a `Path`, `Url` or `Uri`.
     pub entry: Entry,
     /// Extra information associated with the M3U entry.
     pub extinf: ExtInf,
 }
 
 /// Extra information associated with an M3U entry.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ExtInf {
-    /// The duration of the media's runtime in seconds.
+    /// The duration of the media's runtime.
     ///
-    /// Note that some `m3u` extended formats specify streams with a `-1` duration.
-    pub duration_secs: f64,
+    /// Note that extended formats specify live streams with a `-1` duration, read as
+    /// `Duration::Live`.
+    #[cfg_attr(feature = "serde", serde(rename = "duration_secs",
+                                        with = "serde_impls::duration"))]
+    pub duration: Duration,
     /// The name of the media. E.g. "Aphex Twin - Windowlicker".
     pub name: String,
+    /// Any `key="value"` attributes listed between the duration and the name, in order of
+    /// appearance. E.g. `#EXTINF:-1 tvg-id="news" group-title="News",News Channel`.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Vec::is_empty",
+                                        with = "serde_impls::attributes"))]
+    pub attributes: Vec<(String, String)>,
 }
 
 
@@ -63,7 +166,7 @@ impl Entry {
     pub fn is_path(&self) -> bool {
         match *self {
             Entry::Path(_) => true,
-            Entry::Url(_) => false,
+            Entry::Url(_) | Entry::Uri(_) => false,
         }
     }
 
@@ -71,22 +174,74 @@ impl Entry {
     pub fn is_url(&self) -> bool {
         match *self {
             Entry::Url(_) => true,
-            Entry::Path(_) => false,
+            Entry::Path(_) | Entry::Uri(_) => false,
         }
     }
 
-    /// Extend the entry with extra information including the duration in seconds and a name.
-    pub fn extend<N>(self, duration_secs: f64, name: N) -> EntryExt
-        where N: Into<String>,
+    /// Whether or not the `Entry` is an opaque `Uri`.
+    pub fn is_uri(&self) -> bool {
+        match *self {
+            Entry::Uri(_) => true,
+            Entry::Path(_) | Entry::Url(_) => false,
+        }
+    }
+
+    /// Extend the entry with extra information including the duration and a name.
+    ///
+    /// The duration may be given as a `Duration`, a `std::time::Duration` or as seconds, where
+    /// `-1.0` marks a live stream.
+    pub fn extend<D, N>(self, duration: D, name: N) -> EntryExt
+        where D: Into<Duration>,
+              N: Into<String>,
     {
         EntryExt {
-            extinf: ExtInf { duration_secs: duration_secs, name: name.into() },
+            extinf: ExtInf::new(duration, name),
             entry: self,
         }
     }
 
 }
 
+impl ExtInf {
+
+    /// Create an `ExtInf` with the given duration and name and no attributes.
+    ///
+    /// The duration may be given as a `Duration`, a `std::time::Duration` or as seconds, where
+    /// `-1.0` marks a live stream.
+    pub fn new<D, N>(duration: D, name: N) -> Self
+        where D: Into<Duration>,
+              N: Into<String>,
+    {
+        ExtInf {
+            duration: duration.into(),
+            name: name.into(),
+            attributes: Vec::new(),
+        }
+    }
+
+    /// The value of the first attribute with the given key, if there is one.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| k == key).map(|(_, v)| &v[..])
+    }
+
+    /// Set the value of the attribute with the given key.
+    ///
+    /// If the attribute already exists its value is replaced in place, otherwise the attribute is
+    /// appended.
+    pub fn set_attribute<K, V>(&mut self, key: K, value: V)
+        where K: Into<String>,
+              V: Into<String>,
+    {
+        let key = key.into();
+        let value = value.into();
+        match self.attributes.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, v)) => *v = value,
+            None => self.attributes.push((key, value)),
+        }
+    }
+
+}
+
 /// A helper function to simplify creation of the `Entry`'s `Path` variant.
 pub fn path_entry<P>(path: P) -> Entry
     where P: Into<std::path::PathBuf>,
@@ -96,5 +251,5 @@ pub fn path_entry<P>(path: P) -> Entry
 
 /// A helper function to simplify creation of the `Entry`'s `Url` variant.
 pub fn url_entry(url: &str) -> Result<Entry, url::ParseError> {
-    Url::parse(url).map(|url| Entry::Url(url))
+    Url::parse(url).map(Entry::Url)
 }
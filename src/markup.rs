@@ -0,0 +1,311 @@
+use std;
+use std::io::{Read, Write};
+use xml;
+use xml::reader::XmlEvent;
+
+/// An XML element, as read from or written to one of the XML based playlist formats.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Element {
+    /// The qualified name of the element, e.g. `track` or `vlc:item`.
+    pub name: String,
+    /// Namespace declarations made on this element as `(prefix, uri)` pairs.
+    ///
+    /// An empty prefix declares the default namespace.
+    pub namespaces: Vec<(String, String)>,
+    /// The attributes of the element as `(qualified name, value)` pairs, in order of appearance.
+    pub attributes: Vec<(String, String)>,
+    /// The child nodes of the element.
+    pub children: Vec<Node>,
+}
+
+/// A node within an XML `Element`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Node {
+    /// A child element.
+    Element(Element),
+    /// Character data.
+    Text(String),
+}
+
+/// The namespace prefixes that are always implicitly declared.
+const BUILTIN_PREFIXES: [&str; 2] = ["xml", "xmlns"];
+
+
+impl Element {
+
+    /// Create an element with the given qualified name and no attributes or children.
+    pub fn new<N>(name: N) -> Self
+        where N: Into<String>,
+    {
+        Element {
+            name: name.into(),
+            namespaces: Vec::new(),
+            attributes: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// The name of the element without its namespace prefix.
+    pub fn local_name(&self) -> &str {
+        match self.name.find(':') {
+            Some(i) => &self.name[i + 1..],
+            None => &self.name,
+        }
+    }
+
+    /// The value of the attribute with the given name, compared case-insensitively.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| &v[..])
+    }
+
+    /// The child elements of this element.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.children.iter().filter_map(|node| match *node {
+            Node::Element(ref element) => Some(element),
+            Node::Text(_) => None,
+        })
+    }
+
+    /// The first child element with the given local name, compared case-insensitively.
+    pub fn element(&self, local_name: &str) -> Option<&Element> {
+        self.elements().find(|e| e.local_name().eq_ignore_ascii_case(local_name))
+    }
+
+    /// The concatenated, trimmed character data of this element's direct children.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for node in &self.children {
+            if let Node::Text(ref s) = *node {
+                text.push_str(s);
+            }
+        }
+        text.trim().to_string()
+    }
+
+    /// Create an element with the given qualified name containing only the given text.
+    pub(crate) fn with_text<N, T>(name: N, text: T) -> Self
+        where N: Into<String>,
+              T: Into<String>,
+    {
+        let mut element = Element::new(name);
+        element.children.push(Node::Text(text.into()));
+        element
+    }
+
+    /// Append a child element containing only the given text.
+    pub(crate) fn push_text_element<N, T>(&mut self, name: N, text: T)
+        where N: Into<String>,
+              T: Into<String>,
+    {
+        self.children.push(Node::Element(Element::with_text(name, text)));
+    }
+
+}
+
+
+/// Parse the root element of the XML document read from the given reader.
+///
+/// Returns `Ok(None)` if the document ended before the root element was closed.
+pub(crate) fn parse<R>(reader: R) -> Result<Option<Element>, xml::reader::Error>
+    where R: Read,
+{
+    let config = xml::reader::ParserConfig::new()
+        .trim_whitespace(true)
+        .ignore_comments(true)
+        .cdata_to_characters(true);
+
+    // The stack of open elements, each alongside its in-scope namespace declarations.
+    let mut stack: Vec<(Element, Vec<(String, String)>)> = Vec::new();
+    for event in config.create_reader(reader) {
+        match event? {
+            XmlEvent::StartElement { name, attributes, namespace } => {
+                let mut element = Element::new(qualified_name(&name));
+                element.attributes = attributes.iter()
+                    .map(|a| (qualified_name(&a.name), a.value.clone()))
+                    .collect();
+                let scope: Vec<(String, String)> = namespace.0.into_iter()
+                    .filter(|(prefix, uri)| {
+                        !BUILTIN_PREFIXES.contains(&&prefix[..]) && !uri.is_empty()
+                    })
+                    .collect();
+                // Only record the declarations that were not already in scope.
+                element.namespaces = match stack.last() {
+                    None => scope.clone(),
+                    Some((_, parent_scope)) => scope.iter()
+                        .filter(|d| !parent_scope.contains(d))
+                        .cloned()
+                        .collect(),
+                };
+                stack.push((element, scope));
+            },
+            XmlEvent::EndElement { .. } => {
+                let (element, _) = stack.pop().expect("unbalanced XML events");
+                match stack.last_mut() {
+                    Some((parent, _)) => parent.children.push(Node::Element(element)),
+                    None => return Ok(Some(element)),
+                }
+            },
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if let Some((parent, _)) = stack.last_mut() {
+                    parent.children.push(Node::Text(text));
+                }
+            },
+            _ => (),
+        }
+    }
+
+    Ok(None)
+}
+
+/// Produce an element whose namespace declarations include every namespace in `scope` that is
+/// used within `element`, so that it may be written independently of its original ancestors.
+pub(crate) fn detach(element: &Element, scope: &[(String, String)]) -> Element {
+    let mut prefixes = Vec::new();
+    used_prefixes(element, &mut prefixes);
+    let mut detached = element.clone();
+    for declaration in scope {
+        let declared = detached.namespaces.iter().any(|(prefix, _)| *prefix == declaration.0);
+        if !declared && prefixes.contains(&&declaration.0[..]) {
+            detached.namespaces.push(declaration.clone());
+        }
+    }
+    detached
+}
+
+/// Collect the namespace prefixes used by the names of `element`, its attributes and its
+/// descendants. The default namespace is represented by an empty prefix.
+fn used_prefixes<'a>(element: &'a Element, prefixes: &mut Vec<&'a str>) {
+    let prefix = |name: &'a str| name.find(':').map(|i| &name[..i]);
+    prefixes.push(prefix(&element.name).unwrap_or(""));
+    prefixes.extend(element.attributes.iter().filter_map(|(name, _)| prefix(name)));
+    for child in element.elements() {
+        used_prefixes(child, prefixes);
+    }
+}
+
+/// Parse a clock value in seconds such as `"00:05:21.00"`, `"5:21"`, `"321.5"`, `"321s"`,
+/// `"5.35min"`, `"1.5h"` or `"321000ms"`.
+pub(crate) fn parse_clock(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let units: [(&str, f64); 4] = [("ms", 0.001), ("min", 60.0), ("h", 3_600.0), ("s", 1.0)];
+    for &(suffix, scale) in units.iter() {
+        if let Some(value) = text.strip_suffix(suffix) {
+            return value.trim().parse::<f64>().ok().map(|v| v * scale);
+        }
+    }
+    let mut secs = 0.0;
+    for (i, part) in text.split(':').enumerate() {
+        if i > 2 {
+            return None;
+        }
+        secs = secs * 60.0 + part.trim().parse::<f64>().ok()?;
+    }
+    if secs < 0.0 {
+        return None;
+    }
+    Some(secs)
+}
+
+/// Format the given number of seconds as a clock value of the form `hh:mm:ss.fff`.
+pub(crate) fn format_clock(secs: f64) -> String {
+    let ms = (secs * 1_000.0).round() as u64;
+    let (h, m, s, ms) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1_000 % 60, ms % 1_000);
+    format!("{:02}:{:02}:{:02}.{:03}", h, m, s, ms)
+}
+
+/// Write the XML declaration line.
+pub(crate) fn write_declaration<W>(writer: &mut W) -> Result<(), std::io::Error>
+    where W: Write,
+{
+    writeln!(writer, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")
+}
+
+/// Write the start tag of the given element, without its children.
+pub(crate) fn write_start<W>(writer: &mut W, element: &Element) -> Result<(), std::io::Error>
+    where W: Write,
+{
+    write!(writer, "<{}", element.name)?;
+    for (prefix, uri) in &element.namespaces {
+        match &prefix[..] {
+            "" => write!(writer, " xmlns=\"{}\"", escape(uri))?,
+            prefix => write!(writer, " xmlns:{}=\"{}\"", prefix, escape(uri))?,
+        }
+    }
+    for (name, value) in &element.attributes {
+        write!(writer, " {}=\"{}\"", name, escape(value))?;
+    }
+    write!(writer, ">")
+}
+
+/// Write the end tag of the given element.
+pub(crate) fn write_end<W>(writer: &mut W, element: &Element) -> Result<(), std::io::Error>
+    where W: Write,
+{
+    write!(writer, "</{}>", element.name)
+}
+
+/// Write the given element and all of its children, indented by `depth` levels.
+pub(crate) fn write_element<W>(writer: &mut W, element: &Element, depth: usize)
+    -> Result<(), std::io::Error>
+    where W: Write,
+{
+    const INDENT: &str = "  ";
+    for _ in 0..depth {
+        writer.write_all(INDENT.as_bytes())?;
+    }
+    write_start(writer, element)?;
+    let only_text = element.children.iter().all(|node| match *node {
+        Node::Text(_) => true,
+        Node::Element(_) => false,
+    });
+    if only_text {
+        write!(writer, "{}", escape(&element.text()))?;
+    } else {
+        writeln!(writer)?;
+        for node in &element.children {
+            match *node {
+                Node::Element(ref child) => write_element(writer, child, depth + 1)?,
+                Node::Text(ref text) => {
+                    for _ in 0..depth + 1 {
+                        writer.write_all(INDENT.as_bytes())?;
+                    }
+                    writeln!(writer, "{}", escape(text.trim()))?;
+                },
+            }
+        }
+        for _ in 0..depth {
+            writer.write_all(INDENT.as_bytes())?;
+        }
+    }
+    write_end(writer, element)?;
+    writeln!(writer)
+}
+
+/// Escape the given text for use within XML character data or attribute values.
+pub(crate) fn escape(text: &str) -> std::borrow::Cow<'_, str> {
+    if !text.contains(['&', '<', '>', '"', '\'']) {
+        return std::borrow::Cow::Borrowed(text);
+    }
+    let mut escaped = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    std::borrow::Cow::Owned(escaped)
+}
+
+/// The qualified name as written in the source, e.g. `vlc:item`.
+fn qualified_name(name: &xml::name::OwnedName) -> String {
+    match name.prefix {
+        Some(ref prefix) => format!("{}:{}", prefix, name.local_name),
+        None => name.local_name.clone(),
+    }
+}
@@ -0,0 +1,415 @@
+use ExtInf;
+use std;
+
+/// The template used to format a `TrackName` by default.
+pub const DEFAULT_NAME_TEMPLATE: &str = "[%artist% - ]%title%[ (feat. %featuring%)][ (%version%)]";
+
+/// The structured fields of an `ExtInf` name such as
+/// `"Sample artist - Sample title (feat. Guest) (Club Mix)"`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TrackName {
+    /// The main artist, if the name has one.
+    pub artist: Option<String>,
+    /// The title of the track, excluding any featured artists and version.
+    pub title: String,
+    /// The featured artists, from both the artist and the title, in order of appearance.
+    pub featuring: Vec<String>,
+    /// The remix or version of the track, e.g. `Club Mix` or `Live`.
+    pub version: Option<String>,
+}
+
+/// Parses `ExtInf` names into `TrackName`s.
+///
+/// The artist and title are split at the first of the separators found, `" - "` by default. A
+/// featured artist marker such as `feat.` within the artist or title introduces the featured
+/// artists, which are separated by `,` or `&`, either within brackets or until the end of the
+/// artist or title. Within brackets, markers are also recognized without their trailing `.`,
+/// e.g. `(feat Guest)`. Any other bracketed group at the end of the title, which may itself
+/// contain brackets, is taken to be the version.
+///
+/// Names may be normalized as they are read via `ReadOptions::name_parser`.
+///
+/// ```
+/// extern crate m3u;
+///
+/// fn main() {
+///     let name = m3u::NameParser::new().parse("Artist ft. Guest - Title (Extended Mix)");
+///     assert_eq!(name.artist, Some("Artist".to_string()));
+///     assert_eq!(name.title, "Title");
+///     assert_eq!(name.featuring, vec!["Guest".to_string()]);
+///     assert_eq!(name.version, Some("Extended Mix".to_string()));
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameParser {
+    /// The separators between the artist and title.
+    separators: Vec<String>,
+    /// The lower case markers introducing featured artists.
+    featuring_markers: Vec<String>,
+}
+
+/// Formats `TrackName`s into `ExtInf` names according to a template.
+///
+/// The template may contain the fields `%artist%`, `%title%`, `%featuring%` and `%version%`, where
+/// featured artists are joined by `, `. A section within square brackets is only included if all
+/// of the fields within it are non-empty, e.g. `[%artist% - ]%title%` omits the separator for a
+/// track without an artist.
+///
+/// Names may be formatted as they are written via `WriteOptions::name_template`.
+///
+/// ```
+/// extern crate m3u;
+///
+/// fn main() {
+///     let name = m3u::NameParser::new().parse("Title (feat. A, B)");
+///     let template = m3u::NameTemplate::new("%title%[ - %artist%][ with %featuring%]");
+///     assert_eq!(template.format(&name), "Title with A, B");
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NameTemplate {
+    /// The parsed parts of the template.
+    parts: Vec<Part>,
+}
+
+/// A part of a `NameTemplate`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Part {
+    /// Text that is included as is.
+    Literal(String),
+    /// A field of the `TrackName`.
+    Field(Field),
+    /// A section that is only included if all of its fields are non-empty.
+    Optional(Vec<Part>),
+}
+
+/// The fields of a `TrackName` that may appear within a `NameTemplate`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Field {
+    /// `%artist%`.
+    Artist,
+    /// `%title%`.
+    Title,
+    /// `%featuring%`.
+    Featuring,
+    /// `%version%`.
+    Version,
+}
+
+
+impl TrackName {
+
+    /// Format the name via the given template.
+    pub fn format(&self, template: &NameTemplate) -> String {
+        template.format(self)
+    }
+
+}
+
+impl NameParser {
+
+    /// Create a parser splitting at `" - "`, `" – "` or `" — "` and recognizing the featured
+    /// artist markers `featuring`, `feat.` and `ft.`.
+    ///
+    /// The bare `feat` and `ft` are only recognized within brackets, as they may otherwise be
+    /// ordinary words, e.g. `A Feat Of Strength`.
+    pub fn new() -> Self {
+        NameParser {
+            separators: vec![" - ".to_string(), " – ".to_string(), " — ".to_string()],
+            featuring_markers: ["featuring", "feat.", "ft."]
+                .iter()
+                .map(|marker| marker.to_string())
+                .collect(),
+        }
+    }
+
+    /// Split the artist and title at the first of the given separators found.
+    pub fn separators<I>(mut self, separators: I) -> Self
+        where I: IntoIterator,
+              I::Item: Into<String>,
+    {
+        self.separators = separators.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Recognize the given markers introducing featured artists, compared case-insensitively.
+    pub fn featuring_markers<I>(mut self, markers: I) -> Self
+        where I: IntoIterator,
+              I::Item: AsRef<str>,
+    {
+        self.featuring_markers = markers.into_iter()
+            .map(|marker| marker.as_ref().to_lowercase())
+            .collect();
+        self
+    }
+
+    /// Parse the given name.
+    pub fn parse(&self, name: &str) -> TrackName {
+        let name = name.trim();
+        let split = self.separators.iter()
+            .filter(|separator| !separator.is_empty())
+            .filter_map(|separator| name.find(&separator[..]).map(|i| (i, separator.len())))
+            .min();
+        let (artist, title) = match split {
+            Some((i, len)) => (Some(&name[..i]), &name[i + len..]),
+            None => (None, name),
+        };
+
+        let mut featuring = Vec::new();
+        let artist = artist.map(|artist| self.strip_featuring(artist, &mut featuring));
+
+        // Strip the trailing bracketed groups of the title, innermost last.
+        let mut title = title.trim();
+        let mut version = None;
+        let mut title_featuring = Vec::new();
+        while let Some((rest, group)) = trailing_group(title) {
+            match self.marker_end(group, 0, true) {
+                Some(end) => title_featuring.insert(0, &group[end..]),
+                None if version.is_none() => version = Some(group.trim().to_string()),
+                None => break,
+            }
+            title = rest.trim_end();
+        }
+        let title = self.strip_featuring(title, &mut featuring);
+        for names in title_featuring {
+            featuring.extend(split_names(names));
+        }
+
+        TrackName {
+            artist: artist.filter(|artist| !artist.is_empty()),
+            title,
+            featuring,
+            version: version.filter(|version| !version.is_empty()),
+        }
+    }
+
+    /// Remove any unbracketed featured artists from the end of the given text, pushing them onto
+    /// `featuring`.
+    fn strip_featuring(&self, text: &str, featuring: &mut Vec<String>) -> String {
+        let text = text.trim();
+        let start = text.char_indices()
+            .filter(|&(i, c)| c == ' ' && self.marker_end(text, i + 1, false).is_some())
+            .map(|(i, _)| i)
+            .next();
+        match start {
+            Some(i) => {
+                let end = self.marker_end(text, i + 1, false).expect("marker was found");
+                featuring.extend(split_names(&text[end..]));
+                text[..i].trim_end().to_string()
+            },
+            None => text.to_string(),
+        }
+    }
+
+    /// If a featured artist marker followed by a space begins at `start` within `text`, the index
+    /// following the space.
+    ///
+    /// Within brackets, markers are also matched without their trailing `.`.
+    fn marker_end(&self, text: &str, start: usize, bracketed: bool) -> Option<usize> {
+        let rest = text.get(start..)?.to_ascii_lowercase();
+        let is_marker = |marker: &str| {
+            !marker.is_empty() && rest.starts_with(&format!("{} ", marker))
+        };
+        self.featuring_markers.iter()
+            .find_map(|marker| {
+                let bare = marker.trim_end_matches('.');
+                if is_marker(marker) {
+                    Some(marker.len())
+                } else if bracketed && is_marker(bare) {
+                    Some(bare.len())
+                } else {
+                    None
+                }
+            })
+            .map(|len| start + len + 1)
+    }
+
+}
+
+impl NameTemplate {
+
+    /// Parse the given template.
+    ///
+    /// Unrecognized fields and unbalanced brackets are included literally.
+    pub fn new(template: &str) -> Self {
+        let mut stack: Vec<Vec<Part>> = vec![Vec::new()];
+        let mut rest = template;
+        while let Some(c) = rest.chars().next() {
+            match c {
+                '[' => {
+                    stack.push(Vec::new());
+                    rest = &rest[1..];
+                },
+                ']' if stack.len() > 1 => {
+                    let section = stack.pop().expect("stack has a section");
+                    stack.last_mut().expect("stack has a root").push(Part::Optional(section));
+                    rest = &rest[1..];
+                },
+                '%' => {
+                    let end = rest[1..].find('%').map(|i| i + 1);
+                    let field = end.and_then(|end| Field::from_name(&rest[1..end]));
+                    match (field, end) {
+                        (Some(field), Some(end)) => {
+                            stack.last_mut().expect("stack has a part").push(Part::Field(field));
+                            rest = &rest[end + 1..];
+                        },
+                        _ => {
+                            push_literal(stack.last_mut().expect("stack has a part"), "%");
+                            rest = &rest[1..];
+                        },
+                    }
+                },
+                c => {
+                    let len = c.len_utf8();
+                    push_literal(stack.last_mut().expect("stack has a part"), &rest[..len]);
+                    rest = &rest[len..];
+                },
+            }
+        }
+        // Sections that were never closed are included literally.
+        while stack.len() > 1 {
+            let section = stack.pop().expect("stack has a section");
+            let parent = stack.last_mut().expect("stack has a root");
+            push_literal(parent, "[");
+            parent.extend(section);
+        }
+        NameTemplate { parts: stack.pop().expect("stack has a root") }
+    }
+
+    /// Format the given name according to the template.
+    pub fn format(&self, name: &TrackName) -> String {
+        let mut formatted = String::new();
+        format_parts(&self.parts, name, &mut formatted);
+        formatted
+    }
+
+}
+
+impl Field {
+
+    /// The field with the given name, as written between `%`s.
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "artist" => Some(Field::Artist),
+            "title" => Some(Field::Title),
+            "featuring" => Some(Field::Featuring),
+            "version" => Some(Field::Version),
+            _ => None,
+        }
+    }
+
+    /// The value of the field within the given name.
+    fn value(&self, name: &TrackName) -> String {
+        match *self {
+            Field::Artist => name.artist.clone().unwrap_or_default(),
+            Field::Title => name.title.clone(),
+            Field::Featuring => name.featuring.join(", "),
+            Field::Version => name.version.clone().unwrap_or_default(),
+        }
+    }
+
+}
+
+impl ExtInf {
+
+    /// Parse the name of the media via the given parser.
+    pub fn track_name(&self, parser: &NameParser) -> TrackName {
+        parser.parse(&self.name)
+    }
+
+    /// Replace the name of the media with the given name formatted via the given template.
+    pub fn set_track_name(&mut self, name: &TrackName, template: &NameTemplate) {
+        self.name = template.format(name);
+    }
+
+}
+
+
+/// If the given text ends with a bracketed group, the text preceding the group and the text
+/// within it.
+///
+/// The group begins at the bracket balancing the closing one, so that it may contain nested
+/// groups. Returns `None` if the brackets are unbalanced.
+fn trailing_group(text: &str) -> Option<(&str, &str)> {
+    let close = text.chars().last()?;
+    let open = match close {
+        ')' => '(',
+        ']' => '[',
+        _ => return None,
+    };
+    let mut depth = 0;
+    for (i, c) in text.char_indices().rev() {
+        if c == close {
+            depth += 1;
+        } else if c == open {
+            depth -= 1;
+            if depth == 0 {
+                if i == 0 {
+                    return None;
+                }
+                return Some((&text[..i], &text[i + 1..text.len() - 1]));
+            }
+        }
+    }
+    None
+}
+
+/// Split the given list of artists at `,` and `&`.
+fn split_names(names: &str) -> Vec<String> {
+    names.split([',', '&'])
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Push the given text onto the parts, extending a trailing literal if there is one.
+fn push_literal(parts: &mut Vec<Part>, text: &str) {
+    if let Some(Part::Literal(literal)) = parts.last_mut() {
+        literal.push_str(text);
+        return;
+    }
+    parts.push(Part::Literal(text.to_string()));
+}
+
+/// Format the given parts onto `formatted`, returning whether all of their fields were non-empty.
+fn format_parts(parts: &[Part], name: &TrackName, formatted: &mut String) -> bool {
+    let mut complete = true;
+    for part in parts {
+        match *part {
+            Part::Literal(ref literal) => formatted.push_str(literal),
+            Part::Field(field) => {
+                let value = field.value(name);
+                complete &= !value.is_empty();
+                formatted.push_str(&value);
+            },
+            Part::Optional(ref section) => {
+                let mut text = String::new();
+                if format_parts(section, name, &mut text) {
+                    formatted.push_str(&text);
+                }
+            },
+        }
+    }
+    complete
+}
+
+
+impl Default for NameParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for NameTemplate {
+    fn default() -> Self {
+        Self::new(DEFAULT_NAME_TEMPLATE)
+    }
+}
+
+impl std::fmt::Display for TrackName {
+    /// Formats the name via the `DEFAULT_NAME_TEMPLATE`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", NameTemplate::default().format(self))
+    }
+}
@@ -0,0 +1,318 @@
+//! Reading and writing the INI-style **PLS** playlist format.
+//!
+//! A **PLS** file looks like this:
+//!
+//! ```ini
+//! [playlist]
+//! File1=http://emp.cx:8000/Listen.pls
+//! Title1=Example Stream
+//! Length1=-1
+//! File2=Greatest Hits\Example.ogg
+//! Title2=Example Artist - Example title
+//! Length2=321
+//! NumberOfEntries=2
+//! Version=2
+//! ```
+//!
+//! `FileN` maps to the `Entry`, while `TitleN` and `LengthN` map to the `ExtInf` name and
+//! duration respectively. A missing `TitleN` is read as an empty name and a missing `LengthN` is
+//! read as an `Unknown` duration. When writing, an empty name and an `Unknown` duration are
+//! omitted, while a `Live` duration is written as `-1`.
+
+use {Duration, Entry, EntryExt, ExtInf};
+use read::read_entry;
+use std;
+use std::collections::BTreeMap;
+use std::io::{BufRead, Write};
+
+/// A reader that reads the **PLS** format.
+///
+/// As the entries of a **PLS** file are keyed by number rather than by position, the whole
+/// playlist is read upon construction.
+pub struct Reader {
+    /// The entries that have not yet been yielded, in order of their number.
+    entries: std::vec::IntoIter<EntryExt>,
+    /// The value of the `Version` key, if any.
+    version: Option<u32>,
+}
+
+/// A writer that writes the **PLS** format.
+///
+/// The `[playlist]` header is written upon construction and the `NumberOfEntries` and `Version`
+/// keys are written by `finish`. If `finish` is not called, these keys are written when the
+/// writer is dropped, though any errors that occur in the process cannot be handled.
+pub struct Writer<W>
+    where W: Write,
+{
+    /// The writer to which the **PLS** format is written.
+    writer: W,
+    /// The number of entries written so far.
+    count: usize,
+    /// Whether or not the trailing keys have been written.
+    finished: bool,
+}
+
+/// An iterator that yields the `Entry`s of a **PLS** playlist, discarding titles and lengths.
+pub struct Entries<'r> {
+    reader: &'r mut Reader,
+}
+
+/// An iterator that yields the `EntryExt`s of a **PLS** playlist.
+pub struct EntryExts<'r> {
+    reader: &'r mut Reader,
+}
+
+/// Errors that may occur when reading the **PLS** format.
+#[derive(Debug)]
+pub enum Error {
+    /// The `[playlist]` header was not found on the first non-empty line.
+    HeaderNotFound,
+    /// The line with the given number (starting from `1`) was not a `key=value` pair.
+    InvalidLine(usize),
+    /// The `LengthN` value on the line with the given number could not be parsed.
+    InvalidLength(usize),
+    /// Errors produced by the `BufRead::read_line` method.
+    BufRead(std::io::Error),
+}
+
+/// The keys read for a single numbered entry.
+#[derive(Default)]
+struct Numbered {
+    file: Option<Entry>,
+    title: Option<String>,
+    length: Option<f64>,
+}
+
+/// The version written by the `Writer`.
+const VERSION: u32 = 2;
+
+
+impl Reader {
+
+    /// Read a **PLS** playlist from the given reader.
+    pub fn new<R>(mut reader: R) -> Result<Self, Error>
+        where R: BufRead,
+    {
+        // The keys read for each entry, ordered by number.
+        let mut numbered: BTreeMap<u64, Numbered> = BTreeMap::new();
+        let mut version = None;
+        let mut found_header = false;
+        let mut line_buffer = String::new();
+        let mut line_number = 0;
+
+        loop {
+            line_buffer.clear();
+            if reader.read_line(&mut line_buffer)? == 0 {
+                break;
+            }
+            line_number += 1;
+            let line = line_buffer.trim();
+
+            // Skip empty lines and comments.
+            if line.is_empty() || line.starts_with(';') || line.starts_with('#') {
+                continue;
+            }
+
+            // The first line must be the "[playlist]" header.
+            if !found_header {
+                if !line.eq_ignore_ascii_case("[playlist]") {
+                    return Err(Error::HeaderNotFound);
+                }
+                found_header = true;
+                continue;
+            }
+
+            let eq = match line.find('=') {
+                Some(eq) => eq,
+                None => return Err(Error::InvalidLine(line_number)),
+            };
+            let key = line[..eq].trim().to_ascii_lowercase();
+            let value = line[eq + 1..].trim();
+
+            if key == "version" {
+                version = value.parse().ok();
+                continue;
+            }
+
+            // Split keys like "file12" into "file" and `12`, ignoring unknown keys.
+            let digits = key.len() - key.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+            let (name, number) = key.split_at(key.len() - digits);
+            let number: u64 = match number.parse() {
+                Ok(number) => number,
+                Err(_) => continue,
+            };
+            let keys = numbered.entry(number).or_default();
+            match name {
+                "file" => keys.file = Some(read_entry(value)),
+                "title" => keys.title = Some(value.to_string()),
+                "length" => match value.parse() {
+                    Ok(secs) => keys.length = Some(secs),
+                    Err(_) => return Err(Error::InvalidLength(line_number)),
+                },
+                _ => (),
+            }
+        }
+
+        if !found_header {
+            return Err(Error::HeaderNotFound);
+        }
+
+        // Skip any entries without a file.
+        let entries: Vec<_> = numbered.into_values()
+            .filter_map(|keys| {
+                let Numbered { file, title, length } = keys;
+                let length = length.map(Duration::from_secs_f64).unwrap_or(Duration::Unknown);
+                let extinf = ExtInf::new(length, title.unwrap_or_default());
+                file.map(|entry| EntryExt { entry, extinf })
+            })
+            .collect();
+
+        Ok(Reader { entries: entries.into_iter(), version })
+    }
+
+    /// The value of the `Version` key, if one was present.
+    pub fn version(&self) -> Option<u32> {
+        self.version
+    }
+
+    /// Produce an iterator that yields `Entry`s, discarding their titles and lengths.
+    pub fn entries(&mut self) -> Entries<'_> {
+        Entries { reader: self }
+    }
+
+    /// Produce an iterator that yields `EntryExt`s.
+    pub fn entry_exts(&mut self) -> EntryExts<'_> {
+        EntryExts { reader: self }
+    }
+
+    /// Attempts to read a **PLS** playlist from the specified file.
+    ///
+    /// This is a convenience constructor that opens a `File`, wraps it in a `BufReader` and then
+    /// constructs a `Reader` from it.
+    pub fn open<P>(filename: P) -> Result<Self, Error>
+        where P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(filename)?;
+        let buf_reader = std::io::BufReader::new(file);
+        Self::new(buf_reader)
+    }
+
+}
+
+impl<W> Writer<W>
+    where W: Write,
+{
+
+    /// Create a writer that writes the **PLS** format.
+    ///
+    /// The `[playlist]` header line is written immediately.
+    pub fn new(mut writer: W) -> Result<Self, std::io::Error> {
+        writeln!(writer, "[playlist]")?;
+        Ok(Writer { writer, count: 0, finished: false })
+    }
+
+    /// Attempt to write the given `Entry` as the next `FileN` key.
+    pub fn write_entry(&mut self, entry: &Entry) -> Result<(), std::io::Error> {
+        self.count += 1;
+        write_file(&mut self.writer, self.count, entry)
+    }
+
+    /// Attempt to write the given `EntryExt` as the next `FileN`, `TitleN` and `LengthN` keys.
+    ///
+    /// The `TitleN` key is omitted if the name is empty and the `LengthN` key is omitted if the
+    /// duration is `Unknown`.
+    pub fn write_entry_ext(&mut self, entry_ext: &EntryExt) -> Result<(), std::io::Error> {
+        self.count += 1;
+        let n = self.count;
+        write_file(&mut self.writer, n, &entry_ext.entry)?;
+        let extinf = &entry_ext.extinf;
+        if !extinf.name.is_empty() {
+            writeln!(self.writer, "Title{}={}", n, extinf.name)?;
+        }
+        if extinf.duration != Duration::Unknown {
+            writeln!(self.writer, "Length{}={}", n, extinf.duration)?;
+        }
+        Ok(())
+    }
+
+    /// Write the trailing `NumberOfEntries` and `Version` keys and flush the inner writer.
+    pub fn finish(mut self) -> Result<(), std::io::Error> {
+        self.finished = true;
+        write_footer(&mut self.writer, self.count)?;
+        self.writer.flush()
+    }
+
+}
+
+
+/// Write the `FileN` key for the given entry.
+fn write_file<W>(writer: &mut W, n: usize, entry: &Entry) -> Result<(), std::io::Error>
+    where W: Write,
+{
+    match *entry {
+        Entry::Path(ref path) => writeln!(writer, "File{}={}", n, path.display()),
+        Entry::Url(ref url) | Entry::Uri(ref url) => writeln!(writer, "File{}={}", n, url),
+    }
+}
+
+/// Write the trailing `NumberOfEntries` and `Version` keys.
+fn write_footer<W>(writer: &mut W, count: usize) -> Result<(), std::io::Error>
+    where W: Write,
+{
+    writeln!(writer, "NumberOfEntries={}", count)?;
+    writeln!(writer, "Version={}", VERSION)
+}
+
+
+impl<'r> Iterator for Entries<'r> {
+    type Item = Entry;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.entries.next().map(|entry_ext| entry_ext.entry)
+    }
+}
+
+impl<'r> Iterator for EntryExts<'r> {
+    type Item = EntryExt;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.entries.next()
+    }
+}
+
+
+impl<W> Drop for Writer<W>
+    where W: Write,
+{
+    fn drop(&mut self) {
+        if !self.finished {
+            write_footer(&mut self.writer, self.count).ok();
+            self.writer.flush().ok();
+        }
+    }
+}
+
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::BufRead(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::BufRead(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::HeaderNotFound => write!(f, "the \"[playlist]\" header was not found"),
+            Error::InvalidLine(line) => write!(f, "line {} is not a `key=value` pair", line),
+            Error::InvalidLength(line) => write!(f, "the length on line {} is invalid", line),
+            Error::BufRead(ref err) => err.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,307 @@
+use Entry;
+use std;
+use std::path::PathBuf;
+
+/// A path parsed independently of the host OS, recognizing Windows drive letters, UNC paths and
+/// both `\` and `/` as separators.
+///
+/// Playlists are frequently written on one OS and read on another, so a path such as
+/// `C:\Documents and Settings\I\My Music\Sample.mp3` must be understood even where `\` is not a
+/// separator and `C:` is not a prefix.
+///
+/// ```
+/// extern crate m3u;
+///
+/// fn main() {
+///     let path = m3u::PortablePath::parse(r"C:\Documents and Settings\I\My Music\Sample.mp3");
+///     assert_eq!(path.file_name(), Some("Sample.mp3"));
+///
+///     let mapping = m3u::DriveMapping::new().drive('C', "/mnt/c");
+///     let native = path.to_native(&mapping).unwrap();
+///     let expected = "/mnt/c/Documents and Settings/I/My Music/Sample.mp3";
+///     # if cfg!(unix) {
+///     assert_eq!(native, std::path::Path::new(expected));
+///     # }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PortablePath {
+    /// The drive or UNC share at the start of the path, if any.
+    pub prefix: Option<PathPrefix>,
+    /// Whether or not the path begins at the root of its prefix, or of the filesystem if it has no
+    /// prefix. E.g. `true` for `C:\Music` and `/music` but `false` for `C:Music` and `Music`.
+    pub has_root: bool,
+    /// The names separated by `\` or `/`, excluding empty names and `.`.
+    pub components: Vec<String>,
+}
+
+/// The prefix of a Windows path.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathPrefix {
+    /// A drive letter such as the `C` in `C:\Music`, always stored in upper case.
+    Drive(char),
+    /// A UNC share such as `\\server\share`.
+    Unc {
+        /// The name of the server.
+        server: String,
+        /// The name of the share on the server.
+        share: String,
+    },
+}
+
+/// A mapping from Windows drives and UNC shares to the native directories at which they are
+/// mounted, used by `PortablePath::to_native`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DriveMapping {
+    /// Mount points of drive letters, stored in upper case.
+    drives: Vec<(char, PathBuf)>,
+    /// Mount points of UNC shares.
+    shares: Vec<(String, String, PathBuf)>,
+}
+
+
+impl PortablePath {
+
+    /// Parse the given path.
+    pub fn parse(path: &str) -> Self {
+        let is_separator = |c: char| c == '\\' || c == '/';
+        let mut rest = path;
+        let mut prefix = None;
+
+        let bytes = path.as_bytes();
+        if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+            prefix = Some(PathPrefix::Drive(bytes[0].to_ascii_uppercase() as char));
+            rest = &path[2..];
+        } else if path.starts_with("\\\\") || path.starts_with("//") {
+            let mut parts = path[2..].splitn(3, is_separator);
+            let server = parts.next().unwrap_or("").to_string();
+            let share = parts.next().unwrap_or("").to_string();
+            prefix = Some(PathPrefix::Unc { server, share });
+            rest = parts.next().unwrap_or("");
+            let components = split_components(rest);
+            return PortablePath { prefix, has_root: true, components };
+        }
+
+        let has_root = rest.starts_with(is_separator);
+        PortablePath { prefix, has_root, components: split_components(rest) }
+    }
+
+    /// Whether or not the path identifies a location independently of any current directory.
+    ///
+    /// Paths with a root are considered absolute even without a drive, as are UNC paths.
+    pub fn is_absolute(&self) -> bool {
+        self.has_root
+    }
+
+    /// The final component of the path, if there is one and it is not `..`.
+    pub fn file_name(&self) -> Option<&str> {
+        match self.components.last() {
+            Some(name) if name != ".." => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The path without its final component, or `None` if it has no components.
+    pub fn parent(&self) -> Option<PortablePath> {
+        if self.components.is_empty() {
+            return None;
+        }
+        let mut parent = self.clone();
+        parent.components.pop();
+        Some(parent)
+    }
+
+    /// Join the given path onto this path.
+    ///
+    /// If `path` is absolute or has a prefix it is returned as is, except that a rooted path
+    /// without a prefix takes on this path's prefix, as on Windows.
+    pub fn join(&self, path: &PortablePath) -> PortablePath {
+        if path.prefix.is_some() {
+            return path.clone();
+        }
+        if path.has_root {
+            let mut joined = path.clone();
+            joined.prefix = self.prefix.clone();
+            return joined;
+        }
+        let mut joined = self.clone();
+        joined.components.extend(path.components.iter().cloned());
+        joined
+    }
+
+    /// Convert the path to a native path.
+    ///
+    /// Drive letters and UNC shares are replaced by their mount points within `mapping`. Prefixes
+    /// without a mount point are kept on Windows and cause `None` to be returned elsewhere.
+    pub fn to_native(&self, mapping: &DriveMapping) -> Option<PathBuf> {
+        let mut native = match self.prefix {
+            None if self.has_root => PathBuf::from(std::path::MAIN_SEPARATOR.to_string()),
+            None => PathBuf::new(),
+            Some(ref prefix) => match mapping.mount_point(prefix) {
+                Some(mount_point) => mount_point.to_path_buf(),
+                None if cfg!(windows) => PathBuf::from(self.prefix_string('\\') + "\\"),
+                None => return None,
+            },
+        };
+        native.extend(&self.components);
+        Some(native)
+    }
+
+    /// The path with `..` components lexically resolved against the components preceding them.
+    ///
+    /// `..` components at the start of a relative path are kept, while those at the start of a
+    /// rooted path are removed as the root has no parent.
+    pub fn normalize(&self) -> PortablePath {
+        let mut normalized = PortablePath { components: Vec::new(), ..self.clone() };
+        for name in &self.components {
+            let parent = name == "..";
+            match normalized.components.last() {
+                Some(last) if parent && last != ".." => {
+                    normalized.components.pop();
+                },
+                None if parent && self.has_root => (),
+                _ => normalized.components.push(name.clone()),
+            }
+        }
+        normalized
+    }
+
+    /// The relative path that leads from the directory `base` to this path, using `..`
+    /// components as necessary.
+    ///
+    /// Returns `None` unless both paths are absolute with the same prefix. Names are compared
+    /// case-insensitively when the paths have a prefix, as they are then Windows paths.
+    pub fn relative_to(&self, base: &PortablePath) -> Option<PortablePath> {
+        if !self.has_root || !base.has_root {
+            return None;
+        }
+        let case_insensitive = match (&self.prefix, &base.prefix) {
+            (None, None) => false,
+            (Some(PathPrefix::Drive(a)), Some(PathPrefix::Drive(b))) if a == b => true,
+            (Some(PathPrefix::Unc { server: a, share: s }),
+             Some(PathPrefix::Unc { server: b, share: t }))
+                if a.eq_ignore_ascii_case(b) && s.eq_ignore_ascii_case(t) => true,
+            _ => return None,
+        };
+        let eq = |a: &String, b: &String| {
+            if case_insensitive { a.to_lowercase() == b.to_lowercase() } else { a == b }
+        };
+
+        let path = self.normalize();
+        let base = base.normalize();
+        let common = path.components.iter()
+            .zip(&base.components)
+            .take_while(|&(a, b)| eq(a, b))
+            .count();
+        let mut components = vec!["..".to_string(); base.components.len() - common];
+        components.extend(path.components[common..].iter().cloned());
+        Some(PortablePath { prefix: None, has_root: false, components })
+    }
+
+    /// Format the path using the given separator.
+    pub fn to_string_with_separator(&self, separator: char) -> String {
+        let mut string = self.prefix_string(separator);
+        if self.has_root {
+            string.push(separator);
+        }
+        for (i, name) in self.components.iter().enumerate() {
+            if i > 0 {
+                string.push(separator);
+            }
+            string.push_str(name);
+        }
+        string
+    }
+
+    /// The prefix as written within a path using the given separator, e.g. `C:` or
+    /// `\\server\share`.
+    fn prefix_string(&self, separator: char) -> String {
+        match self.prefix {
+            None => String::new(),
+            Some(PathPrefix::Drive(letter)) => format!("{}:", letter),
+            Some(PathPrefix::Unc { ref server, ref share }) =>
+                format!("{0}{0}{1}{0}{2}", separator, server, share),
+        }
+    }
+
+}
+
+impl DriveMapping {
+
+    /// Create an empty mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map the given drive letter to the given mount point.
+    pub fn drive<P>(mut self, letter: char, mount_point: P) -> Self
+        where P: Into<PathBuf>,
+    {
+        let letter = letter.to_ascii_uppercase();
+        self.drives.retain(|&(l, _)| l != letter);
+        self.drives.push((letter, mount_point.into()));
+        self
+    }
+
+    /// Map the given UNC share to the given mount point.
+    ///
+    /// Server and share names are compared case-insensitively.
+    pub fn share<S, T, P>(mut self, server: S, share: T, mount_point: P) -> Self
+        where S: Into<String>,
+              T: Into<String>,
+              P: Into<PathBuf>,
+    {
+        let (server, share) = (server.into(), share.into());
+        self.shares.retain(|(s, t, _)| {
+            !s.eq_ignore_ascii_case(&server) || !t.eq_ignore_ascii_case(&share)
+        });
+        self.shares.push((server, share, mount_point.into()));
+        self
+    }
+
+    /// The mount point of the given prefix, if it is mapped.
+    pub fn mount_point(&self, prefix: &PathPrefix) -> Option<&std::path::Path> {
+        match *prefix {
+            PathPrefix::Drive(letter) => self.drives.iter()
+                .find(|&&(l, _)| l == letter.to_ascii_uppercase())
+                .map(|(_, path)| path.as_path()),
+            PathPrefix::Unc { ref server, ref share } => self.shares.iter()
+                .find(|(s, t, _)| s.eq_ignore_ascii_case(server) && t.eq_ignore_ascii_case(share))
+                .map(|(_, _, path)| path.as_path()),
+        }
+    }
+
+}
+
+impl Entry {
+
+    /// The `Path` of the entry parsed as a `PortablePath`, or `None` if the entry is a `Url` or
+    /// `Uri`.
+    ///
+    /// Parts of the path that are not valid unicode are replaced with `U+FFFD`.
+    pub fn portable_path(&self) -> Option<PortablePath> {
+        match *self {
+            Entry::Path(ref path) => Some(PortablePath::parse(&path.to_string_lossy())),
+            Entry::Url(_) | Entry::Uri(_) => None,
+        }
+    }
+
+}
+
+
+/// Split the given path into its non-empty components, excluding `.`.
+fn split_components(path: &str) -> Vec<String> {
+    path.split(['\\', '/'])
+        .filter(|name| !name.is_empty() && *name != ".")
+        .map(str::to_string)
+        .collect()
+}
+
+
+impl std::fmt::Display for PortablePath {
+    /// Formats the path with `\` separators if it has a prefix, or `/` separators otherwise.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let separator = if self.prefix.is_some() { '\\' } else { '/' };
+        write!(f, "{}", self.to_string_with_separator(separator))
+    }
+}
@@ -1,6 +1,7 @@
-use {Entry, EntryExt, ExtInf};
+use {Base, Entry, EntryExt, ExtInf, LineEnding, NameParser, PathPrefix, PortablePath};
 use std;
 use url;
+use url::percent_encoding::percent_decode;
 
 /// A reader that reads the `M3U` format from the underlying reader.
 ///
@@ -18,10 +19,31 @@ pub struct Reader<R, E>
     reader: R,
     /// String used for buffering read lines.
     line_buffer: String,
+    /// Options that change how entries are interpreted.
+    options: ReadOptions,
+    /// The line ending of the first ended line read, if any.
+    line_ending: Option<LineEnding>,
     /// The entry type that the `reader` will read.
     entry: std::marker::PhantomData<E>,
 }
 
+/// Options that change how a `Reader` interprets the entries that it reads.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReadOptions {
+    /// If `Some`, each entry is resolved against this `Base` via `Entry::resolve` as it is read.
+    pub base: Option<Base>,
+    /// Whether or not `file:` URLs are kept as `Entry::Url`s rather than converted to
+    /// percent-decoded `Entry::Path`s.
+    pub keep_file_urls: bool,
+    /// Whether or not names, attributes and `Path` entries are percent-decoded, recovering the
+    /// values written under `EscapePolicy::Escape`.
+    pub unescape: bool,
+    /// If `Some`, the name of each `EntryExt` is parsed via this parser and normalized to the
+    /// `DEFAULT_NAME_TEMPLATE` as it is read, e.g. `A ft. B - Song` is read as
+    /// `A - Song (feat. B)`.
+    pub name_parser: Option<NameParser>,
+}
+
 /// A `Reader` that specifically reads `Entry`s.
 pub type EntryReader<R> = Reader<R, Entry>;
 /// A `Reader` that specifically reads `EntryExt`s.
@@ -72,14 +94,31 @@ impl<R, E> Reader<R, E>
     where R: std::io::BufRead,
 {
 
-    fn new_inner(reader: R, line_buffer: String) -> Self {
+    fn new_inner(reader: R, line_buffer: String, options: ReadOptions) -> Self {
         Reader {
-            reader: reader,
-            line_buffer: line_buffer,
+            reader,
+            line_ending: LineEnding::detect(&line_buffer),
+            line_buffer,
+            options,
             entry: std::marker::PhantomData,
         }
     }
 
+    /// The line ending of the first line read that was ended with one, if any.
+    ///
+    /// When rewriting the playlist, this may be used as the `WriteOptions::line_ending` in order
+    /// to match the line endings of the original.
+    pub fn line_ending(&self) -> Option<LineEnding> {
+        self.line_ending
+    }
+
+    /// Record the line ending of the line within the `line_buffer` if none has been yet.
+    fn record_line_ending(&mut self) {
+        if self.line_ending.is_none() {
+            self.line_ending = LineEnding::detect(&self.line_buffer);
+        }
+    }
+
     /// Produce the inner `reader`.
     pub fn into_inner(self) -> R {
         self.reader
@@ -93,7 +132,13 @@ impl<R> EntryReader<R>
 
     /// Create a reader that reads the original, non-extended M3U `Entry` type.
     pub fn new(reader: R) -> Self {
-        Self::new_inner(reader, String::new())
+        Self::new_with_options(reader, ReadOptions::default())
+    }
+
+    /// Create a reader that reads the original, non-extended M3U `Entry` type with the given
+    /// options.
+    pub fn new_with_options(reader: R, options: ReadOptions) -> Self {
+        Self::new_inner(reader, String::new(), options)
     }
 
     /// Attempt to read the next `Entry` from the inner reader.
@@ -103,14 +148,14 @@ impl<R> EntryReader<R>
     /// Returns an `Err(std::io::Error)` if an error occurs when calling the inner `reader`'s
     /// `BufRead::read_line` method.
     fn read_next_entry(&mut self) -> Result<Option<Entry>, std::io::Error> {
-        let Reader { ref mut reader, ref mut line_buffer, .. } = *self;
-        read_next_entry(reader, line_buffer)
+        let Reader { ref mut reader, ref mut line_buffer, ref options, .. } = *self;
+        read_next_entry(reader, line_buffer, options)
     }
 
     /// Produce an iterator that yields `Entry`s.
     ///
     /// All `Entry`s are lazily read from the inner buffered reader.
-    pub fn entries(&mut self) -> Entries<R> {
+    pub fn entries(&mut self) -> Entries<'_, R> {
         Entries { reader: self }
     }
 
@@ -125,15 +170,25 @@ impl<R> EntryExtReader<R>
     /// The `#EXTM3U` header is read immediately.
     ///
     /// Reading `EntryExt`s will be done on demand.
-    pub fn new_ext(mut reader: R) -> Result<Self, EntryExtReaderConstructionError> {
+    pub fn new_ext(reader: R) -> Result<Self, EntryExtReaderConstructionError> {
+        Self::new_ext_with_options(reader, ReadOptions::default())
+    }
+
+    /// Create a reader that reads the extended M3U `EntryExt` type with the given options.
+    ///
+    /// The `#EXTM3U` header is read immediately.
+    pub fn new_ext_with_options(mut reader: R, options: ReadOptions)
+        -> Result<Self, EntryExtReaderConstructionError>
+    {
         let mut line_buffer = String::new();
 
         loop {
-            let num_read_bytes = try!(reader.read_line(&mut line_buffer));
-            let line = line_buffer.trim_left();
+            let num_read_bytes = reader.read_line(&mut line_buffer)?;
+            // Skip the byte order mark written by some Windows editors.
+            let line = line_buffer.trim_start_matches('\u{feff}').trim_start();
 
             // The first line of the extended M3U format should always be the "#EXTM3U" header.
-            const HEADER: &'static str = "#EXTM3U";
+            const HEADER: &str = "#EXTM3U";
             if line.len() >= HEADER.len() && &line[..HEADER.len()] == HEADER {
                 break;
             }
@@ -147,7 +202,7 @@ impl<R> EntryExtReader<R>
             return Err(EntryExtReaderConstructionError::HeaderNotFound);
         }
 
-        Ok(Self::new_inner(reader, line_buffer))
+        Ok(Self::new_inner(reader, line_buffer, options))
     }
 
     /// Attempt to read the next `EntryExt` from the inner reader.
@@ -161,9 +216,9 @@ impl<R> EntryExtReader<R>
     ///
     /// Returns `Ok(None)` when there are no more lines.
     fn read_next_entry(&mut self) -> Result<Option<EntryExt>, ReadEntryExtError> {
-        let Reader { ref mut reader, ref mut line_buffer, .. } = *self;
+        let Reader { ref mut reader, ref mut line_buffer, ref options, .. } = *self;
 
-        const TAG: &'static str = "#EXTINF:";
+        const TAG: &str = "#EXTINF:";
 
         // Read an `ExtInf` from the given line.
         //
@@ -171,34 +226,70 @@ impl<R> EntryExtReader<R>
         fn read_extinf(mut line: &str) -> Option<ExtInf> {
             line = &line[TAG.len()..];
 
-            // The duration and track title should be delimited by the first comma.
-            let mut parts = line.splitn(2, ',');
+            // The duration is delimited by the first comma or by the whitespace preceding any
+            // attributes.
+            let end = line.find(|c: char| c == ',' || c.is_whitespace()).unwrap_or(line.len());
+            let duration = line[..end].parse().ok()?;
+            line = &line[end..];
 
-            // Get the duration, or return `None` if there isn't any.
-            let duration_secs = match parts.next().and_then(|s| s.parse().ok()) {
-                Some(secs) => secs,
-                None => return None,
-            };
+            // Read any `key="value"` attributes up until the comma preceding the name.
+            let mut attributes = Vec::new();
+            loop {
+                line = line.trim_start();
+                match line.chars().next() {
+                    None => break,
+                    Some(',') => {
+                        line = &line[1..];
+                        break;
+                    },
+                    Some(_) => {
+                        let (attribute, rest) = read_attribute(line)?;
+                        attributes.push(attribute);
+                        line = rest;
+                    },
+                }
+            }
 
-            // Get the name or set it as an empty string.
-            let name = parts.next().map(|s| s.trim().into()).unwrap_or_else(String::new);
+            // The remainder of the line is the name.
+            let name = line.trim().into();
 
             Some(ExtInf {
-                duration_secs: duration_secs,
-                name: name,
+                duration,
+                name,
+                attributes,
             })
         }
 
+        // Read a single `key="value"` or `key=value` attribute from the start of the given line.
+        //
+        // Returns the attribute along with the remainder of the line.
+        fn read_attribute(line: &str) -> Option<((String, String), &str)> {
+            let eq = line.find('=')?;
+            let key = &line[..eq];
+            if key.is_empty() || key.contains(|c: char| c == ',' || c.is_whitespace()) {
+                return None;
+            }
+            let rest = &line[eq + 1..];
+            let (value, rest) = if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted.find('"')?;
+                (&quoted[..end], &quoted[end + 1..])
+            } else {
+                let end = rest.find(|c: char| c == ',' || c.is_whitespace()).unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            };
+            Some(((key.into(), value.into()), rest))
+        }
+
         // Skip empty lines and comments until we find the "#EXTINF:" tag.
         loop {
             // Read the next line or return `None` if we're done.
             line_buffer.clear();
-            if try!(reader.read_line(line_buffer)) == 0 {
+            if reader.read_line(line_buffer)? == 0 {
                 return Ok(None);
             }
 
             let extinf = {
-                let line = line_buffer.trim_left();
+                let line = line_buffer.trim_start();
 
                 match line.chars().next() {
                     // Skip empty lines.
@@ -208,28 +299,36 @@ impl<R> EntryExtReader<R>
                         // Skip comments.
                         false => continue,
                         // We've found the "#EXTINF:" tag.
-                        true => read_extinf(line),
+                        true => read_extinf(line).map(|mut extinf| {
+                            if options.unescape {
+                                unescape_extinf(&mut extinf);
+                            }
+                            if let Some(ref parser) = options.name_parser {
+                                extinf.name = parser.parse(&extinf.name).to_string();
+                            }
+                            extinf
+                        }),
                     },
                     // Assume the "#EXTINF:" tag was omitted and this was intended to be an `Entry`.
                     // Due to the lack of official specification, it is unclear whether a mixture
                     // of tagged and non-tagged entries should be supported for the EXTM3U format.
                     Some(_) => {
-                        let entry = read_entry(line.trim_right());
+                        let entry = read_entry_with_options(line.trim_end(), options);
                         return Err(ReadEntryExtError::ExtInfNotFound(entry));
                     },
                 }
             };
 
             // Read the next non-empty, non-comment line as an entry.
-            let entry = match try!(read_next_entry(reader, line_buffer)) {
+            let entry = match read_next_entry(reader, line_buffer, options)? {
                 None => return Ok(None),
                 Some(entry) => entry,
             };
 
             return match extinf {
                 Some(extinf) => Ok(Some(EntryExt {
-                    entry: entry,
-                    extinf: extinf,
+                    entry,
+                    extinf,
                 })),
                 None => Err(ReadEntryExtError::ExtInfNotFound(entry)),
             }
@@ -239,7 +338,7 @@ impl<R> EntryExtReader<R>
     /// Produce an iterator that yields `EntryExt`s.
     ///
     /// All `EntryExt`s are lazily read from the inner buffered reader.
-    pub fn entry_exts(&mut self) -> EntryExts<R> {
+    pub fn entry_exts(&mut self) -> EntryExts<'_, R> {
         EntryExts { reader: self }
     }
 
@@ -254,7 +353,7 @@ impl EntryReader<std::io::BufReader<std::fs::File>> {
     pub fn open<P>(filename: P) -> Result<Self, std::io::Error>
         where P: AsRef<std::path::Path>,
     {
-        let file = try!(std::fs::File::open(filename));
+        let file = std::fs::File::open(filename)?;
         let buf_reader = std::io::BufReader::new(file);
         Ok(Self::new(buf_reader))
     }
@@ -270,7 +369,7 @@ impl EntryExtReader<std::io::BufReader<std::fs::File>> {
     pub fn open_ext<P>(filename: P) -> Result<Self, EntryExtReaderConstructionError>
         where P: AsRef<std::path::Path>,
     {
-        let file = try!(std::fs::File::open(filename));
+        let file = std::fs::File::open(filename)?;
         let buf_reader = std::io::BufReader::new(file);
         Self::new_ext(buf_reader)
     }
@@ -279,50 +378,122 @@ impl EntryExtReader<std::io::BufReader<std::fs::File>> {
 
 
 /// Attempt to read the next `Entry` from the inner reader.
-fn read_next_entry<R>(reader: &mut R, line_buffer: &mut String) -> Result<Option<Entry>, std::io::Error>
+fn read_next_entry<R>(reader: &mut R, line_buffer: &mut String, options: &ReadOptions)
+    -> Result<Option<Entry>, std::io::Error>
     where R: std::io::BufRead,
 {
     loop {
         // Read the next line or return `None` if we're done.
         line_buffer.clear();
-        if try!(reader.read_line(line_buffer)) == 0 {
+        if reader.read_line(line_buffer)? == 0 {
             return Ok(None);
         }
 
-        let line = line_buffer.trim_left();
+        let line = line_buffer.trim_start();
         match line.chars().next() {
             // Skip empty lines.
             None => continue,
             // Skip comments.
             Some('#') => continue,
             // Break when we have a non-empty, non-comment line.
-            _ => return Ok(Some(read_entry(line.trim_right()))),
+            _ => return Ok(Some(read_entry_with_options(line.trim_end(), options))),
         }
     }
 }
 
+/// Read an `Entry` from the given line, interpreting it according to the given options.
+fn read_entry_with_options(line: &str, options: &ReadOptions) -> Entry {
+    let mut entry = parse_entry(line, options.keep_file_urls);
+    if options.unescape {
+        // Paths read from `file:` URLs have already been decoded.
+        if let Entry::Path(ref mut path) = entry {
+            if path.as_os_str() == std::ffi::OsStr::new(line) {
+                *path = unescape(line).into();
+            }
+        }
+    }
+    match options.base {
+        Some(ref base) => entry.resolve(base),
+        None => entry,
+    }
+}
+
+/// Percent-decode the name and attributes of the given `ExtInf`.
+fn unescape_extinf(extinf: &mut ExtInf) {
+    extinf.name = unescape(&extinf.name);
+    for &mut (ref mut key, ref mut value) in &mut extinf.attributes {
+        *key = unescape(key);
+        *value = unescape(value);
+    }
+}
+
+/// Percent-decode the given value.
+fn unescape(value: &str) -> String {
+    percent_decode(value.as_bytes()).decode_utf8_lossy().into_owned()
+}
+
+/// The schemes of opaque URIs that are always read as `Entry::Uri`s.
+pub const URI_SCHEMES: &[&str] = &["spotify", "magnet", "urn", "data"];
+
 /// Read an `Entry` from the given line.
 ///
-/// First attempts to read a URL entry. A URL is only returned if `Some` `host_str` is parsed.
+/// First attempts to read a URL entry. `file:` URLs are converted to `Path`s. Other URLs are only
+/// returned as a `Url` if `Some` `host_str` is parsed, or otherwise as an opaque `Uri` if the
+/// scheme is one of `URI_SCHEMES`. Relative paths containing a colon, such as Windows drive
+/// letters or `artist:title.mp3`, are therefore read as `Path`s.
 ///
 /// If a URL cannot be parsed, we assume the entry is a `Path`.
-fn read_entry(line: &str) -> Entry {
+pub(crate) fn read_entry(line: &str) -> Entry {
+    parse_entry(line, false)
+}
+
+/// The same as `read_entry` but optionally keeps `file:` URLs as `Url`s.
+fn parse_entry(line: &str, keep_file_urls: bool) -> Entry {
     if let Ok(url) = url::Url::parse(line) {
+        if url.scheme() == "file" {
+            if keep_file_urls {
+                return Entry::Url(url);
+            }
+            return Entry::Path(file_url_to_path(&url));
+        }
         if url.host_str().is_some() {
             return Entry::Url(url);
         }
-
+        if URI_SCHEMES.contains(&url.scheme()) {
+            return Entry::Uri(url);
+        }
     }
     Entry::Path(line.into())
 }
 
+/// Convert the given `file:` URL to a percent-decoded path.
+///
+/// URLs of Windows paths such as `file:///C:/Music` and `file://server/share` are converted to
+/// `C:\Music` and `\\server\share` respectively, regardless of the host OS.
+fn file_url_to_path(url: &url::Url) -> std::path::PathBuf {
+    let path = percent_decode(url.path().as_bytes()).decode_utf8_lossy();
+    let drive_path = PortablePath::parse(path.get(1..).unwrap_or(""));
+    let is_drive = match drive_path.prefix {
+        Some(PathPrefix::Drive(_)) => drive_path.has_root || drive_path.components.is_empty(),
+        _ => false,
+    };
+    match url.host_str() {
+        Some(host) if !host.is_empty() && host != "localhost" =>
+            format!("\\\\{}{}", host, path.replace('/', "\\")).into(),
+        _ if is_drive => path[1..].replace('/', "\\").into(),
+        _ => path.into_owned().into(),
+    }
+}
+
 
 impl<'r, R> Iterator for Entries<'r, R>
     where R: std::io::BufRead,
 {
     type Item = Result<Entry, std::io::Error>;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.reader.read_next_entry() {
+        let entry = self.reader.read_next_entry();
+        self.reader.record_line_ending();
+        match entry {
             Ok(Some(entry)) => Some(Ok(entry)),
             Ok(None) => None,
             Err(err) => Some(Err(err)),
@@ -335,7 +506,9 @@ impl<'r, R> Iterator for EntryExts<'r, R>
 {
     type Item = Result<EntryExt, ReadEntryExtError>;
     fn next(&mut self) -> Option<Self::Item> {
-        match self.reader.read_next_entry() {
+        let entry = self.reader.read_next_entry();
+        self.reader.record_line_ending();
+        match entry {
             Ok(Some(entry)) => Some(Ok(entry)),
             Ok(None) => None,
             Err(err) => Some(Err(err)),
@@ -358,15 +531,7 @@ impl From<std::io::Error> for ReadEntryExtError {
 
 
 impl std::error::Error for EntryExtReaderConstructionError {
-    fn description(&self) -> &str {
-        match *self {
-            EntryExtReaderConstructionError::HeaderNotFound =>
-                "the \"#EXTM3U\" header was not found",
-            EntryExtReaderConstructionError::BufRead(ref err) =>
-                std::error::Error::description(err),
-        }
-    }
-    fn cause(&self) -> Option<&std::error::Error> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
             EntryExtReaderConstructionError::HeaderNotFound => None,
             EntryExtReaderConstructionError::BufRead(ref err) => Some(err),
@@ -375,15 +540,7 @@ impl std::error::Error for EntryExtReaderConstructionError {
 }
 
 impl std::error::Error for ReadEntryExtError {
-    fn description(&self) -> &str {
-        match *self {
-            ReadEntryExtError::ExtInfNotFound(_) =>
-                "the \"#EXTINF:\" tag was not found or was incorrectly formatted",
-            ReadEntryExtError::BufRead(ref err) =>
-                std::error::Error::description(err),
-        }
-    }
-    fn cause(&self) -> Option<&std::error::Error> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match *self {
             ReadEntryExtError::ExtInfNotFound(_) => None,
             ReadEntryExtError::BufRead(ref err) => Some(err),
@@ -396,7 +553,7 @@ impl std::fmt::Display for EntryExtReaderConstructionError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match *self {
             EntryExtReaderConstructionError::HeaderNotFound =>
-                write!(f, "{}", std::error::Error::description(self)),
+                write!(f, "the \"#EXTM3U\" header was not found"),
             EntryExtReaderConstructionError::BufRead(ref err) =>
                 err.fmt(f),
         }
@@ -407,7 +564,7 @@ impl std::fmt::Display for ReadEntryExtError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         match *self {
             ReadEntryExtError::ExtInfNotFound(_) =>
-                write!(f, "{}", std::error::Error::description(self)),
+                write!(f, "the \"#EXTINF:\" tag was not found or was incorrectly formatted"),
             ReadEntryExtError::BufRead(ref err) =>
                 err.fmt(f),
         }
@@ -0,0 +1,211 @@
+use {Entry, OpenError, PathPrefix, PortablePath};
+use detect;
+use read::read_entry;
+use std;
+use std::path::{Path, PathBuf};
+
+/// Rewrites the prefixes of `Entry::Path`s according to an ordered list of rules, e.g. after a
+/// music library moves from `D:\More Music` to `/mnt/music`.
+///
+/// Paths are compared as `PortablePath`s, so `\` and `/` separators are interchangeable, and
+/// names are compared case-insensitively unless `case_sensitive` is set. The first matching rule
+/// is applied.
+///
+/// ```
+/// extern crate m3u;
+///
+/// fn main() {
+///     let remapper = m3u::Remapper::new().rule(r"D:\More Music", "/mnt/music");
+///     let entry = m3u::path_entry(r"d:\more music\Foo.mp3");
+///     assert_eq!(remapper.remap_entry(&entry), Some(m3u::path_entry("/mnt/music/Foo.mp3")));
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Remapper {
+    /// The `(from, to)` prefix rules in order of precedence.
+    rules: Vec<(PortablePath, PortablePath)>,
+    /// Whether or not names are compared case-sensitively.
+    case_sensitive: bool,
+}
+
+/// The result of remapping the entries of a playlist.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RemapReport {
+    /// The number of entries that matched a rule and were rewritten.
+    pub remapped: usize,
+    /// The indices of the `Path` entries that matched no rule and were left unchanged.
+    pub unmatched: Vec<usize>,
+}
+
+
+impl Remapper {
+
+    /// Create a remapper without any rules that compares names case-insensitively.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a rule that replaces the prefix `from` with `to`.
+    ///
+    /// The rewritten path uses `\` separators if `to` begins with a drive letter or UNC share and
+    /// `/` separators otherwise.
+    pub fn rule(mut self, from: &str, to: &str) -> Self {
+        self.rules.push((PortablePath::parse(from), PortablePath::parse(to)));
+        self
+    }
+
+    /// Whether or not names are compared case-sensitively. Drive letters are always compared
+    /// case-insensitively.
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Apply the first matching rule to the given entry.
+    ///
+    /// Returns `None` if the entry is a `Url` or if no rule matches.
+    pub fn remap_entry(&self, entry: &Entry) -> Option<Entry> {
+        let path = entry.portable_path()?;
+        self.rules.iter().find_map(|(from, to)| {
+            if !self.starts_with(&path, from) {
+                return None;
+            }
+            let mut remapped = to.clone();
+            remapped.components.extend(path.components[from.components.len()..].iter().cloned());
+            Some(Entry::Path(PathBuf::from(remapped.to_string())))
+        })
+    }
+
+    /// Apply the first matching rule to each of the given entries in place.
+    ///
+    /// ```
+    /// extern crate m3u;
+    ///
+    /// fn main() {
+    ///     let mut playlist = m3u::open_any("tests/mixed.m3u").unwrap();
+    ///     let remapper = m3u::Remapper::new().rule(r"D:\More Music", "/mnt/music");
+    ///     let report = remapper.remap(playlist.entries.iter_mut().map(|e| &mut e.entry));
+    ///     assert_eq!(report.remapped, 1);
+    /// }
+    /// ```
+    pub fn remap<'a, I>(&self, entries: I) -> RemapReport
+        where I: IntoIterator<Item = &'a mut Entry>,
+    {
+        let mut report = RemapReport::default();
+        for (index, entry) in entries.into_iter().enumerate() {
+            match self.remap_entry(entry) {
+                Some(remapped) => {
+                    *entry = remapped;
+                    report.remapped += 1;
+                },
+                None => if entry.is_path() {
+                    report.unmatched.push(index);
+                },
+            }
+        }
+        report
+    }
+
+    /// Remap the entries of the playlist file at the given path, rewriting the file in place.
+    ///
+    /// **M3U** based playlists, including HLS playlists, are rewritten line by line so that all
+    /// comments and tags are preserved. Lines that are not valid UTF-8, such as those of legacy
+    /// Latin-1 playlists, are never remapped and are written back byte for byte.
+    ///
+    /// Playlists of other formats are read via `read_any` and written back in the same format via
+    /// `Playlist::write`, which does not preserve information beyond that of a `Playlist`.
+    ///
+    /// The file is only written if at least one entry was remapped.
+    pub fn remap_file<P>(&self, path: P) -> Result<RemapReport, OpenError>
+        where P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let format = detect::detect_format(&bytes).ok_or(OpenError::UnknownFormat)?;
+
+        let (report, output) = if format.is_m3u() {
+            self.remap_m3u(&bytes)
+        } else {
+            let mut playlist = detect::read_any(&bytes[..])?;
+            let report = self.remap(playlist.entries.iter_mut().map(|e| &mut e.entry));
+            let mut output = Vec::new();
+            playlist.write(format, &mut output)?;
+            (report, output)
+        };
+
+        if report.remapped > 0 {
+            std::fs::write(path, output)?;
+        }
+        Ok(report)
+    }
+
+    /// Remap each of the given playlist files in place via `remap_file`.
+    ///
+    /// A failure to remap one file does not prevent the remaining files from being remapped.
+    pub fn remap_files<I>(&self, paths: I) -> Vec<(PathBuf, Result<RemapReport, OpenError>)>
+        where I: IntoIterator,
+              I::Item: AsRef<Path>,
+    {
+        paths.into_iter()
+            .map(|path| {
+                let path = path.as_ref();
+                (path.to_path_buf(), self.remap_file(path))
+            })
+            .collect()
+    }
+
+    /// Remap each entry line of the given **M3U** text, leaving all other lines untouched.
+    fn remap_m3u(&self, bytes: &[u8]) -> (RemapReport, Vec<u8>) {
+        let mut report = RemapReport::default();
+        let mut output = Vec::with_capacity(bytes.len());
+        let mut index = 0;
+        for line in bytes.split_inclusive(|&byte| byte == b'\n') {
+            let content = line.trim_ascii();
+            if content.is_empty() || content.starts_with(b"#") {
+                output.extend_from_slice(line);
+                continue;
+            }
+            let remapped = std::str::from_utf8(content).ok()
+                .map(read_entry)
+                .map(|entry| (self.remap_entry(&entry), entry.is_path()));
+            match remapped {
+                Some((Some(Entry::Path(path)), _)) => {
+                    let start = line.len() - line.trim_ascii_start().len();
+                    output.extend_from_slice(&line[..start]);
+                    output.extend_from_slice(path.to_string_lossy().as_bytes());
+                    output.extend_from_slice(&line[start + content.len()..]);
+                    report.remapped += 1;
+                },
+                _ => {
+                    // Lines that are not valid UTF-8 are assumed to be paths.
+                    if remapped.is_none_or(|(_, is_path)| is_path) {
+                        report.unmatched.push(index);
+                    }
+                    output.extend_from_slice(line);
+                },
+            }
+            index += 1;
+        }
+        (report, output)
+    }
+
+    /// Whether or not `path` begins with the prefix `from`.
+    fn starts_with(&self, path: &PortablePath, from: &PortablePath) -> bool {
+        let eq = |a: &str, b: &str| {
+            if self.case_sensitive { a == b } else { a.to_lowercase() == b.to_lowercase() }
+        };
+        let prefix_eq = match (&path.prefix, &from.prefix) {
+            (None, None) => true,
+            (Some(PathPrefix::Drive(a)), Some(PathPrefix::Drive(b))) => a == b,
+            (Some(PathPrefix::Unc { server: a, share: s }),
+             Some(PathPrefix::Unc { server: b, share: t })) =>
+                a.eq_ignore_ascii_case(b) && eq(s, t),
+            _ => false,
+        };
+        prefix_eq
+            && path.has_root == from.has_root
+            && path.components.len() >= from.components.len()
+            && path.components.iter().zip(&from.components).all(|(a, b)| eq(a, b))
+    }
+
+}
@@ -0,0 +1,97 @@
+use {Entry, PortablePath};
+use std;
+use std::path::{Path, PathBuf};
+use url::Url;
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+
+/// The location against which relative entries are resolved.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Base {
+    /// Relative paths are resolved against the given directory.
+    Dir(PathBuf),
+    /// Relative paths and URL references are resolved against the given URL following the rules
+    /// of `Url::join`, i.e. against the directory of the URL's last path segment.
+    Url(Url),
+}
+
+
+impl Base {
+
+    /// The base for entries of the playlist stored at the given path: the playlist's directory.
+    pub fn from_playlist_path<P>(path: P) -> Self
+        where P: AsRef<Path>,
+    {
+        let dir = path.as_ref().parent().unwrap_or_else(|| Path::new(""));
+        Base::Dir(dir.to_path_buf())
+    }
+
+    /// The base for entries of the playlist fetched from the given URL.
+    pub fn from_playlist_url(url: Url) -> Self {
+        Base::Url(url)
+    }
+
+}
+
+impl Entry {
+
+    /// Resolve the entry against the given `Base`.
+    ///
+    /// - A relative path resolved against a `Base::Dir` is joined to the directory.
+    /// - A path resolved against a `Base::Url` becomes a `Url` via `Url::join`, so that
+    ///   `Greatest Hits\Example.ogg` within `http://host/lists/a.m3u` resolves to
+    ///   `http://host/lists/Greatest%20Hits/Example.ogg`. Each segment of the path is
+    ///   percent-encoded first, so that characters such as `#`, `?` and `%` remain part of the
+    ///   file name. Paths beginning with `/` resolve against the host's root.
+    /// - Absolute paths, paths beginning with a Windows drive letter, UNC paths such as
+    ///   `\\server\share\a.mp3` or any other path beginning with two separators, `Url`s and
+    ///   `Uri`s are returned unchanged, as are paths that cannot be joined to the base URL.
+    ///
+    /// Backslashes within relative paths are treated as separators, except when resolving against
+    /// a `Base::Dir` on Windows where they already are.
+    pub fn resolve(&self, base: &Base) -> Entry {
+        let path = match *self {
+            Entry::Path(ref path) => path,
+            Entry::Url(_) | Entry::Uri(_) => return self.clone(),
+        };
+        let text = path.to_string_lossy();
+        // A leading pair of separators would otherwise be joined to a URL as its host.
+        let is_separator = |c: char| c == '/' || c == '\\';
+        let is_unc = text.starts_with(is_separator) && text[1..].starts_with(is_separator);
+        if is_unc || PortablePath::parse(&text).prefix.is_some() {
+            return self.clone();
+        }
+        match *base {
+            Base::Dir(ref dir) => {
+                if path.is_absolute() || text.starts_with('\\') {
+                    return self.clone();
+                }
+                Entry::Path(dir.join(native_separators(path)))
+            },
+            Base::Url(ref url) => match url.join(&encode_segments(&text)) {
+                Ok(url) => Entry::Url(url),
+                Err(_) => self.clone(),
+            },
+        }
+    }
+
+}
+
+
+/// Percent-encode each `/` or `\` separated segment of the given path, joining them with `/`.
+fn encode_segments(path: &str) -> String {
+    let segments: Vec<String> = path.split(['/', '\\'])
+        .map(|segment| utf8_percent_encode(segment, PATH_SEGMENT_ENCODE_SET).collect())
+        .collect();
+    segments.join("/")
+}
+
+/// Convert any backslash separators within the given relative path to the native separator.
+fn native_separators(path: &Path) -> PathBuf {
+    if std::path::MAIN_SEPARATOR == '\\' {
+        return path.to_path_buf();
+    }
+    match path.to_str() {
+        Some(s) if s.contains('\\') => PathBuf::from(s.replace('\\', "/")),
+        _ => path.to_path_buf(),
+    }
+}
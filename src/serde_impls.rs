@@ -0,0 +1,84 @@
+//! Serialization helpers for types that don't directly implement `Serialize` and `Deserialize`.
+
+/// (De)serialize a `url::Url` as a string.
+pub mod url {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use serde::de::Error;
+    use url::Url;
+
+    pub fn serialize<S>(url: &Url, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer,
+    {
+        serializer.serialize_str(url.as_str())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Url, D::Error>
+        where D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Url::parse(&s).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serialize `ExtInf` attributes as a map, preserving their order.
+pub mod attributes {
+    use serde::{Deserializer, Serializer};
+    use serde::de::{MapAccess, Visitor};
+    use std::fmt;
+
+    #[allow(clippy::ptr_arg)]
+    pub fn serialize<S>(attributes: &Vec<(String, String)>, serializer: S)
+        -> Result<S::Ok, S::Error>
+        where S: Serializer,
+    {
+        serializer.collect_map(attributes.iter().map(|(k, v)| (k, v)))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<(String, String)>, D::Error>
+        where D: Deserializer<'de>,
+    {
+        struct AttributesVisitor;
+
+        impl<'de> Visitor<'de> for AttributesVisitor {
+            type Value = Vec<(String, String)>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "a map of attribute keys to values")
+            }
+
+            fn visit_map<M>(self, mut map: M) -> Result<Self::Value, M::Error>
+                where M: MapAccess<'de>,
+            {
+                let mut attributes = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some(attribute) = map.next_entry()? {
+                    attributes.push(attribute);
+                }
+                Ok(attributes)
+            }
+        }
+
+        deserializer.deserialize_map(AttributesVisitor)
+    }
+}
+
+/// (De)serialize a `Duration` as seconds, with `-1` for `Live` and `null` for `Unknown`.
+pub mod duration {
+    use Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer,
+    {
+        match *duration {
+            Duration::Unknown => serializer.serialize_none(),
+            duration => serializer.serialize_f64(duration.to_playlist_secs()),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+        where D: Deserializer<'de>,
+    {
+        let secs = Option::<f64>::deserialize(deserializer)?;
+        Ok(secs.map(Duration::from_secs_f64).unwrap_or(Duration::Unknown))
+    }
+}
@@ -0,0 +1,332 @@
+//! Filling `ExtInf`s from the tags and stream durations of local media files.
+//!
+//! Tags are read from ID3v2, Vorbis comments (**FLAC**, **Ogg**), MP4 atoms and RIFF INFO chunks,
+//! and durations are calculated from the stream headers, all via the pure-Rust
+//! [symphonia](https://crates.io/crates/symphonia) decoders. The media itself is not decoded.
+//!
+//! ```no_run
+//! extern crate m3u;
+//!
+//! fn main() {
+//!     let extinf = m3u::tags::extinf("Sample.mp3").unwrap();
+//!     println!("{} ({}s)", extinf.name, extinf.duration);
+//! }
+//! ```
+
+use {Base, Duration, Entry, EntryExt, ExtInf};
+use std;
+use std::path::Path;
+use symphonia;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+
+/// The tags and duration read from a media file.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tags {
+    /// The track's artist, or the album's artist if the track has none.
+    pub artist: Option<String>,
+    /// The track's title.
+    pub title: Option<String>,
+    /// The title of the album on which the track appears.
+    pub album: Option<String>,
+    /// The duration of the default stream.
+    pub duration: Option<std::time::Duration>,
+}
+
+/// The result of comparing the listed durations of a playlist's entries with those of their media
+/// files.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DurationReport {
+    /// The number of `Path` entries whose media duration was read.
+    pub checked: usize,
+    /// The entries whose listed duration differs from that of their media by more than the
+    /// tolerance, in order.
+    pub mismatches: Vec<DurationMismatch>,
+    /// The indices of the `Path` entries whose media duration could not be read.
+    pub unreadable: Vec<usize>,
+}
+
+/// An entry whose listed duration differs from that of its media.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DurationMismatch {
+    /// The index of the entry within the playlist.
+    pub index: usize,
+    /// The duration listed by the entry's `ExtInf`.
+    pub listed: Duration,
+    /// The duration of the entry's media.
+    pub actual: std::time::Duration,
+}
+
+/// Errors that may occur while reading the tags of a media file.
+#[derive(Debug)]
+pub enum Error {
+    /// Errors produced when opening the file.
+    Io(std::io::Error),
+    /// The file could not be probed, e.g. as its format is not supported.
+    Symphonia(symphonia::core::errors::Error),
+}
+
+
+impl Tags {
+
+    /// The `ExtInf` describing the media.
+    ///
+    /// The name is `Artist - Title`, only the title if there is no artist, or `fallback_name` if
+    /// there is no title.
+    pub fn to_extinf(&self, fallback_name: &str) -> ExtInf {
+        let name = match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
+            (None, Some(title)) => title.clone(),
+            (_, None) => fallback_name.to_string(),
+        };
+        let duration = self.duration.map(Duration::Known);
+        ExtInf::new(duration.unwrap_or(Duration::Unknown), name)
+    }
+
+    /// Apply the tags of the given metadata revision, keeping any tags that are already set.
+    fn apply(&mut self, revision: &MetadataRevision) {
+        let mut album_artist = None;
+        for tag in revision.tags() {
+            let field = match tag.std_key {
+                Some(StandardTagKey::Artist) => &mut self.artist,
+                Some(StandardTagKey::AlbumArtist) => &mut album_artist,
+                Some(StandardTagKey::TrackTitle) => &mut self.title,
+                Some(StandardTagKey::Album) => &mut self.album,
+                _ => continue,
+            };
+            // RIFF INFO values keep their NUL terminators.
+            let value = tag.value.to_string();
+            let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+            if field.is_none() && !value.is_empty() {
+                *field = Some(value.to_string());
+            }
+        }
+        if self.artist.is_none() {
+            self.artist = album_artist;
+        }
+    }
+
+}
+
+
+/// Read the tags and duration of the media file at the given path.
+///
+/// The file's extension is used as a hint to its format, which is otherwise detected from its
+/// content.
+pub fn read<P>(path: P) -> Result<Tags, Error>
+    where P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = std::fs::File::open(path)?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(extension);
+    }
+    let mut probed = symphonia::default::get_probe().format(
+        &hint,
+        stream,
+        &FormatOptions::default(),
+        &MetadataOptions::default(),
+    )?;
+
+    // Tags within the container take precedence over those preceding it, e.g. ID3v2.
+    let mut tags = Tags::default();
+    if let Some(revision) = probed.format.metadata().current() {
+        tags.apply(revision);
+    }
+    if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        tags.apply(revision);
+    }
+
+    if let Some(track) = probed.format.default_track() {
+        let params = &track.codec_params;
+        if let (Some(n_frames), Some(time_base)) = (params.n_frames, params.time_base) {
+            let time = time_base.calc_time(n_frames);
+            let secs = time.seconds as f64 + time.frac;
+            tags.duration = Some(std::time::Duration::from_secs_f64(secs));
+        }
+    }
+    Ok(tags)
+}
+
+/// The `ExtInf` describing the media file at the given path, named after its file stem if it has
+/// no title tag.
+pub fn extinf<P>(path: P) -> Result<ExtInf, Error>
+    where P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let tags = read(path)?;
+    Ok(tags.to_extinf(&file_stem(path)))
+}
+
+/// Upgrade the entries of a plain playlist into extended entries described by the tags of their
+/// media files.
+///
+/// `Path` entries are resolved against `base` to locate their media, though the entries
+/// themselves are kept as they are. Entries whose tags cannot be read, along with `Url` and `Uri`
+/// entries, have an `Unknown` duration and are named after their file stem.
+///
+/// ```no_run
+/// extern crate m3u;
+///
+/// fn main() {
+///     let mut reader = m3u::Reader::open("playlist.m3u").unwrap();
+///     let entries: Vec<_> = reader.entries().map(|entry| entry.unwrap()).collect();
+///     let base = m3u::Base::from_playlist_path("playlist.m3u");
+///     let entry_exts = m3u::tags::upgrade(entries, &base);
+///
+///     let file = std::fs::File::create("playlist.m3u").unwrap();
+///     let mut writer = m3u::Writer::new_ext(file).unwrap();
+///     for entry_ext in &entry_exts {
+///         writer.write_entry(entry_ext).unwrap();
+///     }
+/// }
+/// ```
+pub fn upgrade<I>(entries: I, base: &Base) -> Vec<EntryExt>
+    where I: IntoIterator<Item = Entry>,
+{
+    entries.into_iter()
+        .map(|entry| {
+            let extinf = match entry.resolve(base) {
+                Entry::Path(ref path) => extinf(path).ok(),
+                Entry::Url(_) | Entry::Uri(_) => None,
+            };
+            let extinf = extinf.unwrap_or_else(|| {
+                ExtInf::new(Duration::Unknown, entry_stem(&entry))
+            });
+            EntryExt { entry, extinf }
+        })
+        .collect()
+}
+
+/// Compare the listed duration of each `Path` entry with the duration of its media file.
+///
+/// `Path` entries are resolved against `base` to locate their media. Durations differing by more
+/// than `tolerance_secs` are reported as mismatches, as are listed durations that are not
+/// `Known`. `Url` and `Uri` entries are not checked.
+///
+/// ```no_run
+/// extern crate m3u;
+///
+/// fn main() {
+///     let mut reader = m3u::Reader::open_ext("playlist.m3u").unwrap();
+///     let entry_exts: Vec<_> = reader.entry_exts().map(|entry| entry.unwrap()).collect();
+///     let base = m3u::Base::from_playlist_path("playlist.m3u");
+///     let report = m3u::tags::verify_durations(&entry_exts, &base, 1.0);
+///     for mismatch in &report.mismatches {
+///         println!("entry {}: listed {}s, actually {}s",
+///                  mismatch.index, mismatch.listed, mismatch.actual.as_secs_f64());
+///     }
+/// }
+/// ```
+pub fn verify_durations<'a, I>(entry_exts: I, base: &Base, tolerance_secs: f64) -> DurationReport
+    where I: IntoIterator<Item = &'a EntryExt>,
+{
+    let mut report = DurationReport::default();
+    for (index, entry_ext) in entry_exts.into_iter().enumerate() {
+        check_duration(index, entry_ext, base, tolerance_secs, &mut report);
+    }
+    report
+}
+
+/// The same as `verify_durations`, but also rewrites the listed duration of each mismatched
+/// entry with the duration of its media.
+pub fn fix_durations<'a, I>(entry_exts: I, base: &Base, tolerance_secs: f64) -> DurationReport
+    where I: IntoIterator<Item = &'a mut EntryExt>,
+{
+    let mut report = DurationReport::default();
+    for (index, entry_ext) in entry_exts.into_iter().enumerate() {
+        let mismatch = check_duration(index, entry_ext, base, tolerance_secs, &mut report);
+        if let Some(actual) = mismatch {
+            entry_ext.extinf.duration = Duration::Known(actual);
+        }
+    }
+    report
+}
+
+
+/// Check the listed duration of the given entry, returning the actual duration on a mismatch.
+fn check_duration(
+    index: usize,
+    entry_ext: &EntryExt,
+    base: &Base,
+    tolerance_secs: f64,
+    report: &mut DurationReport,
+) -> Option<std::time::Duration>
+{
+    let path = match entry_ext.entry.resolve(base) {
+        Entry::Path(path) => path,
+        Entry::Url(_) | Entry::Uri(_) => return None,
+    };
+    let actual = match read(&path).ok().and_then(|tags| tags.duration) {
+        Some(actual) => actual,
+        None => {
+            report.unreadable.push(index);
+            return None;
+        },
+    };
+    report.checked += 1;
+    let listed = entry_ext.extinf.duration;
+    match listed.as_secs_f64() {
+        Some(listed_secs) if (listed_secs - actual.as_secs_f64()).abs() <= tolerance_secs => {
+            return None;
+        },
+        _ => (),
+    }
+    report.mismatches.push(DurationMismatch { index, listed, actual });
+    Some(actual)
+}
+
+/// The file stem of the given path, or an empty string if it has none.
+fn file_stem(path: &Path) -> String {
+    match path.file_stem() {
+        Some(stem) => stem.to_string_lossy().into_owned(),
+        None => String::new(),
+    }
+}
+
+/// The file stem of the last path segment of the given entry.
+fn entry_stem(entry: &Entry) -> String {
+    match *entry {
+        Entry::Path(ref path) => file_stem(path),
+        Entry::Url(ref url) => {
+            let segment = url.path_segments().and_then(|mut segments| segments.next_back());
+            file_stem(Path::new(segment.unwrap_or("")))
+        },
+        Entry::Uri(_) => String::new(),
+    }
+}
+
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<symphonia::core::errors::Error> for Error {
+    fn from(err: symphonia::core::errors::Error) -> Self {
+        Error::Symphonia(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            Error::Symphonia(ref err) => Some(err),
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::Io(ref err) => err.fmt(f),
+            Error::Symphonia(ref err) => err.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use {Base, Entry};
+use std;
+use std::path::{Path, PathBuf};
+
+/// The result of checking the `Path` entries of a playlist against the filesystem.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ValidationReport {
+    /// The number of `Path` entries that were checked. `Url` and `Uri` entries are not
+    /// checked.
+    pub checked: usize,
+    /// The problems found, in order of the entries in which they were found.
+    pub problems: Vec<PathProblem>,
+}
+
+/// A problem found with one of the `Path` entries of a playlist.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PathProblem {
+    /// The index of the entry within the playlist.
+    pub index: usize,
+    /// The path of the entry after resolving it against the playlist's directory.
+    pub path: PathBuf,
+    /// The kind of problem that was found.
+    pub kind: PathProblemKind,
+}
+
+/// The kinds of problems that may be found with a `Path` entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PathProblemKind {
+    /// Nothing exists at the path.
+    Missing,
+    /// The path points to a directory, which a media player would expand into its contents.
+    Directory,
+    /// The file exists but could not be accessed due to a lack of permission.
+    PermissionDenied,
+    /// Some other error occurred when accessing the path.
+    Io(std::io::ErrorKind),
+}
+
+
+impl ValidationReport {
+
+    /// Whether or not every checked path points to an accessible file.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+
+    /// The problems of the given kind.
+    pub fn problems_of_kind(&self, kind: PathProblemKind) -> impl Iterator<Item = &PathProblem> {
+        self.problems.iter().filter(move |problem| problem.kind == kind)
+    }
+
+}
+
+
+/// Check that each `Path` entry of the playlist at `playlist_path` points to an accessible file.
+///
+/// Relative paths are resolved against the directory containing the playlist. The playlist file
+/// itself need not exist.
+///
+/// ```no_run
+/// extern crate m3u;
+///
+/// fn main() {
+///     let mut reader = m3u::Reader::open("playlist.m3u").unwrap();
+///     let entries: Vec<_> = reader.entries().map(|entry| entry.unwrap()).collect();
+///     let report = m3u::validate_paths("playlist.m3u", &entries);
+///     for problem in &report.problems {
+///         println!("entry {}: {:?} {}", problem.index, problem.kind, problem.path.display());
+///     }
+/// }
+/// ```
+pub fn validate_paths<'a, P, I>(playlist_path: P, entries: I) -> ValidationReport
+    where P: AsRef<Path>,
+          I: IntoIterator<Item = &'a Entry>,
+{
+    let paths = resolve_paths(playlist_path.as_ref(), entries);
+    let problems = paths.iter()
+        .filter_map(|&(index, ref path)| check_path(index, path))
+        .collect();
+    ValidationReport { checked: paths.len(), problems }
+}
+
+/// The same as `validate_paths` but checks the paths in parallel, which may be considerably
+/// faster for large playlists, particularly those residing on network filesystems.
+#[cfg(feature = "parallel")]
+pub fn validate_paths_parallel<'a, P, I>(playlist_path: P, entries: I) -> ValidationReport
+    where P: AsRef<Path>,
+          I: IntoIterator<Item = &'a Entry>,
+{
+    use rayon::prelude::*;
+    let paths = resolve_paths(playlist_path.as_ref(), entries);
+    let problems = paths.par_iter()
+        .filter_map(|&(index, ref path)| check_path(index, path))
+        .collect();
+    ValidationReport { checked: paths.len(), problems }
+}
+
+
+/// Resolve each `Path` entry against the directory of the given playlist, alongside its index.
+fn resolve_paths<'a, I>(playlist_path: &Path, entries: I) -> Vec<(usize, PathBuf)>
+    where I: IntoIterator<Item = &'a Entry>,
+{
+    let base = Base::from_playlist_path(playlist_path);
+    entries.into_iter()
+        .enumerate()
+        .filter_map(|(index, entry)| match entry.resolve(&base) {
+            Entry::Path(path) => Some((index, path)),
+            Entry::Url(_) | Entry::Uri(_) => None,
+        })
+        .collect()
+}
+
+/// Check that the given path points to an accessible file.
+fn check_path(index: usize, path: &Path) -> Option<PathProblem> {
+    let result = std::fs::metadata(path).and_then(|metadata| {
+        if metadata.is_dir() {
+            return Ok(Some(PathProblemKind::Directory));
+        }
+        // Opening the file catches permission problems that the metadata alone does not.
+        std::fs::File::open(path).map(|_| None)
+    });
+    let kind = match result {
+        Ok(None) => return None,
+        Ok(Some(kind)) => kind,
+        Err(err) => match err.kind() {
+            std::io::ErrorKind::NotFound => PathProblemKind::Missing,
+            std::io::ErrorKind::PermissionDenied => PathProblemKind::PermissionDenied,
+            kind => PathProblemKind::Io(kind),
+        },
+    };
+    Some(PathProblem { index, path: path.to_path_buf(), kind })
+}
@@ -0,0 +1,289 @@
+//! Reading and writing the SMIL-based Windows Media Player **WPL** playlist format.
+//!
+//! A **WPL** file looks like this:
+//!
+//! ```xml
+//! <?wpl version="1.0"?>
+//! <smil>
+//!   <head>
+//!     <title>Example Playlist</title>
+//!   </head>
+//!   <body>
+//!     <seq>
+//!       <media src="..\Other Music\Bar.mp3" title="Bar" dur="00:03:21.000" />
+//!     </seq>
+//!   </body>
+//! </smil>
+//! ```
+//!
+//! Each `<media>` within the `<body>` maps to an `EntryExt`:
+//!
+//! - `src` maps to the `Entry`.
+//! - The SMIL `title` attribute maps to the `ExtInf` name.
+//! - The SMIL `dur` attribute maps to the `ExtInf` duration. A missing duration is read as an
+//!   `Unknown` duration, while `Live` and `Unknown` durations are written without one.
+//!
+//! Element and attribute names are matched case-insensitively.
+
+use {Duration, Entry, EntryExt, ExtInf};
+use markup::{self, Element};
+use read::read_entry;
+use std;
+use std::io::{Read, Write};
+use xml;
+
+/// A reader that reads the **WPL** format.
+///
+/// The whole document is read upon construction.
+pub struct Reader {
+    /// The `<title>` of the playlist, if any.
+    title: Option<String>,
+    /// The entries that have not yet been yielded.
+    entries: std::vec::IntoIter<EntryExt>,
+}
+
+/// A writer that writes the **WPL** format.
+///
+/// Everything up to and including the opening `<seq>` tag is written upon construction and the
+/// closing tags are written by `finish`. If `finish` is not called, the closing tags are written
+/// when the writer is dropped, though any errors that occur in the process cannot be handled.
+pub struct Writer<W>
+    where W: Write,
+{
+    /// The writer to which the **WPL** format is written.
+    writer: W,
+    /// Whether or not the closing tags have been written.
+    finished: bool,
+}
+
+/// An iterator that yields the `Entry`s of a **WPL** playlist.
+pub struct Entries<'r> {
+    reader: &'r mut Reader,
+}
+
+/// An iterator that yields the `EntryExt`s of a **WPL** playlist.
+pub struct EntryExts<'r> {
+    reader: &'r mut Reader,
+}
+
+/// Errors that may occur when reading the **WPL** format.
+#[derive(Debug)]
+pub enum Error {
+    /// The document was not well-formed XML.
+    Xml(xml::reader::Error),
+    /// The root element of the document was not a `<smil>`.
+    SmilNotFound,
+    /// The `dur` of a `<media>` element was not a valid clock value.
+    InvalidDuration(String),
+    /// Errors produced when opening a file.
+    Io(std::io::Error),
+}
+
+
+impl Reader {
+
+    /// Read a **WPL** playlist from the given reader.
+    pub fn new<R>(reader: R) -> Result<Self, Error>
+        where R: Read,
+    {
+        let root = match markup::parse(reader)? {
+            Some(root) if root.local_name().eq_ignore_ascii_case("smil") => root,
+            _ => return Err(Error::SmilNotFound),
+        };
+
+        let title = root.element("head").and_then(|h| h.element("title")).map(Element::text);
+        let mut entries = Vec::new();
+        if let Some(body) = root.element("body") {
+            read_media(body, &mut entries)?;
+        }
+
+        Ok(Reader { title, entries: entries.into_iter() })
+    }
+
+    /// Attempts to read a **WPL** playlist from the specified file.
+    ///
+    /// This is a convenience constructor that opens a `File`, wraps it in a `BufReader` and then
+    /// constructs a `Reader` from it.
+    pub fn open<P>(filename: P) -> Result<Self, Error>
+        where P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(filename)?;
+        let buf_reader = std::io::BufReader::new(file);
+        Self::new(buf_reader)
+    }
+
+    /// The `<title>` of the playlist, if one was present.
+    pub fn title(&self) -> Option<&str> {
+        self.title.as_ref().map(|s| &s[..])
+    }
+
+    /// Produce an iterator that yields `Entry`s, discarding their titles and durations.
+    pub fn entries(&mut self) -> Entries<'_> {
+        Entries { reader: self }
+    }
+
+    /// Produce an iterator that yields `EntryExt`s.
+    pub fn entry_exts(&mut self) -> EntryExts<'_> {
+        EntryExts { reader: self }
+    }
+
+}
+
+impl<W> Writer<W>
+    where W: Write,
+{
+
+    /// Create a writer that writes a **WPL** playlist without a title.
+    pub fn new(writer: W) -> Result<Self, std::io::Error> {
+        Self::with_title(writer, None)
+    }
+
+    /// Create a writer that writes a **WPL** playlist with the given title.
+    ///
+    /// Everything up to and including the opening `<seq>` tag is written immediately.
+    pub fn with_title(mut writer: W, title: Option<&str>) -> Result<Self, std::io::Error> {
+        writeln!(writer, "<?wpl version=\"1.0\"?>")?;
+        writeln!(writer, "<smil>")?;
+        let mut head = Element::new("head");
+        if let Some(title) = title {
+            head.push_text_element("title", title);
+        }
+        markup::write_element(&mut writer, &head, 1)?;
+        writeln!(writer, "  <body>")?;
+        writeln!(writer, "    <seq>")?;
+        Ok(Writer { writer, finished: false })
+    }
+
+    /// Attempt to write the given `Entry` as a `<media>` element with only a `src`.
+    pub fn write_entry(&mut self, entry: &Entry) -> Result<(), std::io::Error> {
+        markup::write_element(&mut self.writer, &media_element(entry), 3)
+    }
+
+    /// Attempt to write the given `EntryExt` as a `<media>` element.
+    pub fn write_entry_ext(&mut self, entry_ext: &EntryExt) -> Result<(), std::io::Error> {
+        let extinf = &entry_ext.extinf;
+        let mut element = media_element(&entry_ext.entry);
+        if !extinf.name.is_empty() {
+            element.attributes.push(("title".to_string(), extinf.name.clone()));
+        }
+        if let Some(secs) = extinf.duration.as_secs_f64() {
+            let dur = markup::format_clock(secs);
+            element.attributes.push(("dur".to_string(), dur));
+        }
+        markup::write_element(&mut self.writer, &element, 3)
+    }
+
+    /// Write the closing tags and flush the inner writer.
+    pub fn finish(mut self) -> Result<(), std::io::Error> {
+        self.finished = true;
+        write_footer(&mut self.writer)?;
+        self.writer.flush()
+    }
+
+}
+
+
+/// Read all `<media>` elements within the given element in document order.
+fn read_media(element: &Element, entries: &mut Vec<EntryExt>) -> Result<(), Error> {
+    for child in element.elements() {
+        if !child.local_name().eq_ignore_ascii_case("media") {
+            read_media(child, entries)?;
+            continue;
+        }
+        let entry = match child.attribute("src") {
+            Some(src) => read_entry(src.trim()),
+            None => continue,
+        };
+        let name = child.attribute("title").unwrap_or("");
+        let duration = match child.attribute("dur") {
+            None => Duration::Unknown,
+            Some(dur) => match markup::parse_clock(dur) {
+                Some(secs) => Duration::from_secs_f64(secs),
+                None => return Err(Error::InvalidDuration(dur.to_string())),
+            },
+        };
+        entries.push(EntryExt { entry, extinf: ExtInf::new(duration, name) });
+    }
+    Ok(())
+}
+
+/// Produce the `<media src>` element for the given `Entry`.
+fn media_element(entry: &Entry) -> Element {
+    let src = match *entry {
+        Entry::Path(ref path) => path.display().to_string(),
+        Entry::Url(ref url) | Entry::Uri(ref url) => url.to_string(),
+    };
+    let mut element = Element::new("media");
+    element.attributes.push(("src".to_string(), src));
+    element
+}
+
+/// Write the closing `</seq>`, `</body>` and `</smil>` tags.
+fn write_footer<W>(writer: &mut W) -> Result<(), std::io::Error>
+    where W: Write,
+{
+    writeln!(writer, "    </seq>")?;
+    writeln!(writer, "  </body>")?;
+    writeln!(writer, "</smil>")
+}
+
+
+impl<'r> Iterator for Entries<'r> {
+    type Item = Entry;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.entries.next().map(|entry_ext| entry_ext.entry)
+    }
+}
+
+impl<'r> Iterator for EntryExts<'r> {
+    type Item = EntryExt;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.entries.next()
+    }
+}
+
+
+impl<W> Drop for Writer<W>
+    where W: Write,
+{
+    fn drop(&mut self) {
+        if !self.finished {
+            write_footer(&mut self.writer).ok();
+            self.writer.flush().ok();
+        }
+    }
+}
+
+
+impl From<xml::reader::Error> for Error {
+    fn from(err: xml::reader::Error) -> Self {
+        Error::Xml(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Xml(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            Error::SmilNotFound | Error::InvalidDuration(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::Xml(ref err) => err.fmt(f),
+            Error::SmilNotFound => write!(f, "the root `<smil>` element was not found"),
+            Error::InvalidDuration(ref dur) => write!(f, "`{}` is not a valid duration", dur),
+            Error::Io(ref err) => err.fmt(f),
+        }
+    }
+}
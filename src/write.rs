@@ -1,6 +1,9 @@
-use {Entry, EntryExt};
+use {Duration, Entry, EntryExt, NameParser, NameTemplate, PathPrefix, PortablePath};
+use read::read_entry;
 use std;
 use std::io::Write;
+use std::path::PathBuf;
+use url::percent_encoding::{utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
 
 /// A writer that accepts entries of type `E` and writes the associated M3U format.
 ///
@@ -12,10 +15,152 @@ pub struct Writer<W, E>
     writer: W,
     /// Used for buffering lines as bytes for writing.
     line_buffer: Vec<u8>,
+    /// Options that change how entries are written.
+    options: WriteOptions,
+    /// Whether or not the line ending of the last line written has been deferred until the next
+    /// line, as the final line is written without one.
+    pending_line_ending: bool,
     /// The type of entries that will be written.
     entry: std::marker::PhantomData<E>,
 }
 
+/// Options that change how a `Writer` writes entries.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WriteOptions {
+    /// How `Path` entries are written relative to the playlist.
+    pub paths: PathMode,
+    /// The separator used between the components of `Path` entries.
+    ///
+    /// If `None`, paths written verbatim keep their separators while rewritten paths use `\` if
+    /// the original path did or if they begin with a Windows drive or UNC share, and `/`
+    /// otherwise.
+    pub separator: Option<Separator>,
+    /// Whether or not absolute `Path` entries are written as percent-encoded `file://` URLs, e.g.
+    /// `file:///C:/My%20Music/Sample.mp3`. Relative paths are written as paths.
+    pub file_urls: bool,
+    /// How the durations of `EntryExt`s are written.
+    pub durations: DurationFormat,
+    /// How names, attributes and `Path` entries that cannot be written as they are are handled.
+    pub escape: EscapePolicy,
+    /// If `Some`, the name of each `EntryExt` is parsed via `NameParser::new()` and written
+    /// formatted via this template, e.g. `%title% by %artist%`.
+    pub name_template: Option<NameTemplate>,
+    /// The line ending written after each line.
+    ///
+    /// When rewriting an existing playlist, `Reader::line_ending` may be used to match its line
+    /// endings.
+    pub line_ending: LineEnding,
+    /// Whether or not the final line is written without a line ending.
+    ///
+    /// The line ending of each line is then only written once the following line is written.
+    pub omit_final_line_ending: bool,
+}
+
+/// How `Path` entries are written relative to the playlist.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum PathMode {
+    /// Paths are written as they are.
+    #[default]
+    Verbatim,
+    /// Absolute paths are written relative to the given directory, which should be the absolute
+    /// directory of the playlist being written, using `..` components as necessary.
+    ///
+    /// Relative paths and absolute paths on a different drive are written as they are.
+    Relative(PathBuf),
+    /// Relative paths are assumed to be relative to the given directory, which should be the
+    /// directory of the playlist being written, and are written as absolute paths.
+    ///
+    /// Absolute paths are written as they are, with any `..` components resolved.
+    Absolute(PathBuf),
+}
+
+/// The separator written between the components of `Path` entries.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Separator {
+    /// `/`, as used by most devices and non-Windows platforms.
+    Slash,
+    /// `\`, as used by Windows.
+    Backslash,
+}
+
+/// How the durations of `EntryExt`s are written within `#EXTINF:` tags.
+///
+/// `Live` and `Unknown` durations are written as `-1` by all formats other than `Hls`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DurationFormat {
+    /// The shortest decimal number that reads back as the same duration, e.g. `123` or `0.3`.
+    #[default]
+    Shortest,
+    /// The number of seconds rounded to the nearest integer, as required by some older players.
+    Integer,
+    /// The number of seconds rounded to the given number of decimal places, e.g. `123.500` for
+    /// three places.
+    Decimals(usize),
+    /// A decimal floating-point number with millisecond precision, e.g. `9.009`, as recommended
+    /// for the segments of an HLS media playlist.
+    ///
+    /// HLS does not permit negative durations, so `Live` and `Unknown` durations are written as
+    /// `0.000`.
+    Hls,
+}
+
+/// The line ending written after each line of a playlist.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// `\n`, as used by most platforms and as preferred within version control.
+    #[default]
+    Lf,
+    /// `\r\n`, as required by some car stereos and older Windows players.
+    Crlf,
+}
+
+/// How a `Writer` handles values that would not read back as they were written.
+///
+/// These are names, attribute values and `Path` entries containing a line break, names and `Path`
+/// entries with leading or trailing whitespace, which would be trimmed, `Path` entries beginning
+/// with `#`, which would be read as comments, `Path` entries that would be read as a URL or URI,
+/// attribute values containing `"`, and attribute keys that are empty or contain whitespace, `=`,
+/// `,` or `"`. Commas within names and quoted attribute values are written as they are.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EscapePolicy {
+    /// Writing such a value fails with an `InvalidInput` error wrapping an `UnsafeValueError`.
+    #[default]
+    Reject,
+    /// Such characters, along with every `%`, are percent-encoded, e.g. a line break as `%0A`.
+    /// Each `:` of a `Path` that would be read as a URL or URI is encoded as `%3A`.
+    ///
+    /// The original values are recovered by reading with `ReadOptions::unescape`. Empty attribute
+    /// keys cannot be escaped and are rejected.
+    Escape,
+    /// Such characters are replaced: line breaks within names, values and paths with a space,
+    /// `"` within values with `'`, and characters within keys with `_`. Names and paths are
+    /// trimmed, and a path beginning with `#` or that would be read as a URL or URI is preceded by
+    /// `./`.
+    Sanitize,
+}
+
+/// A value that could not be written under `EscapePolicy::Reject`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct UnsafeValueError {
+    /// The kind of value that could not be written.
+    pub field: UnsafeField,
+    /// The value that could not be written.
+    pub value: String,
+}
+
+/// The kinds of value that may be rejected by a `Writer`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum UnsafeField {
+    /// The name of an `ExtInf`.
+    Name,
+    /// The key of an `ExtInf` attribute.
+    AttributeKey,
+    /// The value of an `ExtInf` attribute.
+    AttributeValue,
+    /// A `Path` entry.
+    Path,
+}
+
 /// A `Writer` that specifically writes `Entry`s.
 pub type EntryWriter<W> = Writer<W, Entry>;
 /// A `Writer` that specifically writes `EntryExt`s.
@@ -25,14 +170,40 @@ impl<W, E> Writer<W, E>
     where W: Write,
 {
 
-    fn new_inner(writer: W, line_buffer: Vec<u8>) -> Self {
+    fn new_inner(writer: W, line_buffer: Vec<u8>, options: WriteOptions) -> Self {
         Writer {
-            writer: writer,
-            line_buffer: line_buffer,
+            writer,
+            line_buffer,
+            options,
+            pending_line_ending: false,
             entry: std::marker::PhantomData,
         }
     }
 
+    /// Write the newline-terminated lines within the `line_buffer` to the `writer`, ending each
+    /// with the configured line ending.
+    fn write_lines(&mut self) -> Result<(), std::io::Error> {
+        let Writer { ref mut writer, ref line_buffer, ref options, .. } = *self;
+        let ending = options.line_ending.as_str().as_bytes();
+        let lines = line_buffer.strip_suffix(b"\n").unwrap_or(line_buffer);
+        let mut bytes = Vec::with_capacity(line_buffer.len() * 2);
+        if self.pending_line_ending {
+            bytes.extend_from_slice(ending);
+        }
+        for (i, line) in lines.split(|&byte| byte == b'\n').enumerate() {
+            if i > 0 {
+                bytes.extend_from_slice(ending);
+            }
+            bytes.extend_from_slice(line);
+        }
+        if !options.omit_final_line_ending {
+            bytes.extend_from_slice(ending);
+        }
+        writer.write_all(&bytes)?;
+        self.pending_line_ending = options.omit_final_line_ending;
+        Ok(())
+    }
+
     /// `Flush` the `writer` output stream, ensuring that all intermediately buffered entries reach
     /// their destination.
     ///
@@ -46,23 +217,52 @@ impl<W, E> Writer<W, E>
 
 }
 
+impl LineEnding {
+
+    /// The line ending of the first line within the given text, if any line is ended.
+    pub fn detect(text: &str) -> Option<Self> {
+        let end = text.find('\n')?;
+        match text[..end].ends_with('\r') {
+            true => Some(LineEnding::Crlf),
+            false => Some(LineEnding::Lf),
+        }
+    }
+
+    /// The line ending as a string.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
+    }
+
+}
+
 impl<W> EntryWriter<W>
     where W: Write,
 {
 
     /// Create a writer that writes the original, non_extended M3U `Entry` type.
     pub fn new(writer: W) -> Self {
-        Self::new_inner(writer, Vec::new())
+        Self::new_with_options(writer, WriteOptions::default())
+    }
+
+    /// Create a writer that writes the original, non_extended M3U `Entry` type with the given
+    /// options.
+    pub fn new_with_options(writer: W, options: WriteOptions) -> Self {
+        Self::new_inner(writer, Vec::new(), options)
     }
 
     /// Attempt to write the given `Entry` to the given `writer`.
     ///
     /// Writes the `Path` or `Url` in plain text, ending with a newline.
     pub fn write_entry(&mut self, entry: &Entry) -> Result<(), std::io::Error> {
-        let Writer { ref mut writer, ref mut line_buffer, .. } = *self;
-        line_buffer.clear();
-        try!(write_entry(line_buffer, entry));
-        writer.write_all(line_buffer)
+        {
+            let Writer { ref mut line_buffer, ref options, .. } = *self;
+            line_buffer.clear();
+            write_entry(line_buffer, entry, options)?;
+        }
+        self.write_lines()
     }
 
 }
@@ -74,23 +274,47 @@ impl<W> EntryExtWriter<W>
     /// Create a writer that writes extended M3U `EntryExt`s.
     ///
     /// The `#EXTM3U` header line is written immediately.
-    pub fn new_ext(mut writer: W) -> Result<Self, std::io::Error> {
+    pub fn new_ext(writer: W) -> Result<Self, std::io::Error> {
+        Self::new_ext_with_options(writer, WriteOptions::default())
+    }
+
+    /// Create a writer that writes extended M3U `EntryExt`s with the given options.
+    ///
+    /// The `#EXTM3U` header line is written immediately.
+    pub fn new_ext_with_options(writer: W, options: WriteOptions)
+        -> Result<Self, std::io::Error>
+    {
         let mut line_buffer = Vec::new();
-        try!(writeln!(&mut line_buffer, "#EXTM3U"));
-        try!(writer.write_all(&line_buffer));
-        Ok(Self::new_inner(writer, line_buffer))
+        writeln!(&mut line_buffer, "#EXTM3U")?;
+        let mut writer = Self::new_inner(writer, line_buffer, options);
+        writer.write_lines()?;
+        Ok(writer)
     }
 
     /// Attempt to write the given `EntryExt` to the given `writer`.
     ///
     /// First writes the `#EXTINF:` line, then writes the entry line.
     pub fn write_entry(&mut self, entry_ext: &EntryExt) -> Result<(), std::io::Error> {
-        let Writer { ref mut writer, ref mut line_buffer, .. } = *self;
-        line_buffer.clear();
-        let extinf = &entry_ext.extinf;
-        try!(writeln!(line_buffer, "#EXTINF:{},{}", extinf.duration_secs, &extinf.name));
-        try!(write_entry(line_buffer, &entry_ext.entry));
-        writer.write_all(line_buffer)
+        {
+            let Writer { ref mut line_buffer, ref options, .. } = *self;
+            line_buffer.clear();
+            let extinf = &entry_ext.extinf;
+            let duration = format_duration(extinf.duration, options.durations);
+            write!(line_buffer, "#EXTINF:{}", duration)?;
+            for (key, value) in &extinf.attributes {
+                let key = escape(key, UnsafeField::AttributeKey, options.escape)?;
+                let value = escape(value, UnsafeField::AttributeValue, options.escape)?;
+                write!(line_buffer, " {}=\"{}\"", key, value)?;
+            }
+            let name = match options.name_template {
+                Some(ref template) => template.format(&NameParser::new().parse(&extinf.name)),
+                None => extinf.name.clone(),
+            };
+            let name = escape(&name, UnsafeField::Name, options.escape)?;
+            writeln!(line_buffer, ",{}", name)?;
+            write_entry(line_buffer, &entry_ext.entry, options)?;
+        }
+        self.write_lines()
     }
 
 }
@@ -98,13 +322,180 @@ impl<W> EntryExtWriter<W>
 /// Write the given `Entry` into the given `line_buffer`.
 ///
 /// Writes the `Path` or `Url` in plain text, ending with a newline.
-fn write_entry(line_buffer: &mut Vec<u8>, entry: &Entry) -> Result<(), std::io::Error> {
+fn write_entry(line_buffer: &mut Vec<u8>, entry: &Entry, options: &WriteOptions)
+    -> Result<(), std::io::Error>
+{
     match *entry {
-        Entry::Path(ref path) => writeln!(line_buffer, "{}", path.display()),
-        Entry::Url(ref url) => writeln!(line_buffer, "{}", url),
+        Entry::Path(ref path) => {
+            let path = match rewrite_path(path, options) {
+                // `file://` URLs are already percent-encoded.
+                Some(ref rewritten) if options.file_urls && rewritten.has_root => {
+                    return writeln!(line_buffer, "{}", file_url(rewritten));
+                },
+                Some(rewritten) => {
+                    let separator = path_separator(path, &rewritten, options.separator);
+                    rewritten.to_string_with_separator(separator)
+                },
+                None => path.display().to_string(),
+            };
+            writeln!(line_buffer, "{}", escape(&path, UnsafeField::Path, options.escape)?)
+        },
+        Entry::Url(ref url) | Entry::Uri(ref url) => writeln!(line_buffer, "{}", url),
+    }
+}
+
+/// Format the given duration according to the given format.
+fn format_duration(duration: Duration, format: DurationFormat) -> String {
+    let secs = match (duration.as_secs_f64(), format) {
+        (Some(secs), _) => secs,
+        (None, DurationFormat::Hls) => 0.0,
+        (None, _) => return duration.to_string(),
+    };
+    match format {
+        DurationFormat::Shortest => duration.to_string(),
+        DurationFormat::Integer => format!("{}", secs.round()),
+        DurationFormat::Decimals(places) => format!("{:.*}", places, secs),
+        DurationFormat::Hls => format!("{:.3}", secs),
+    }
+}
+
+/// Rewrite the given path according to the given options.
+///
+/// Returns `None` if the path should be written verbatim.
+fn rewrite_path(path: &std::path::Path, options: &WriteOptions) -> Option<PortablePath> {
+    if options.paths == PathMode::Verbatim && options.separator.is_none() && !options.file_urls {
+        return None;
+    }
+    let portable = PortablePath::parse(&path.to_string_lossy());
+    let rewritten = match options.paths {
+        PathMode::Verbatim => portable,
+        PathMode::Relative(ref dir) => {
+            let dir = PortablePath::parse(&dir.to_string_lossy());
+            portable.relative_to(&dir).unwrap_or(portable)
+        },
+        PathMode::Absolute(ref dir) => {
+            let dir = PortablePath::parse(&dir.to_string_lossy());
+            dir.join(&portable).normalize()
+        },
+    };
+    Some(rewritten)
+}
+
+/// The separator with which the given rewritten form of the given path is written.
+///
+/// Unless a separator is given, backslashes are kept for paths that used them or that have a
+/// Windows prefix.
+fn path_separator(path: &std::path::Path, rewritten: &PortablePath, separator: Option<Separator>)
+    -> char
+{
+    match separator {
+        Some(Separator::Slash) => '/',
+        Some(Separator::Backslash) => '\\',
+        None if path.to_string_lossy().contains('\\') || rewritten.prefix.is_some() => '\\',
+        None => '/',
+    }
+}
+
+/// Prepare the given value for writing as the given field according to the given policy.
+fn escape(value: &str, field: UnsafeField, policy: EscapePolicy)
+    -> Result<String, std::io::Error>
+{
+    let is_empty_key = field == UnsafeField::AttributeKey && value.is_empty();
+    let is_uri = field == UnsafeField::Path && reads_as_uri(value);
+    let has_unsafe = is_uri || value.char_indices().any(|(i, c)| is_unsafe(field, value, i, c));
+    let mut escaped = String::with_capacity(value.len());
+    match policy {
+        EscapePolicy::Reject | EscapePolicy::Escape if is_empty_key => (),
+        EscapePolicy::Reject | EscapePolicy::Sanitize if !is_empty_key && !has_unsafe => {
+            return Ok(value.into());
+        },
+        EscapePolicy::Reject => (),
+        EscapePolicy::Escape => {
+            for (i, c) in value.char_indices() {
+                if c == '%' || (is_uri && c == ':') || is_unsafe(field, value, i, c) {
+                    let mut bytes = [0; 4];
+                    for byte in c.encode_utf8(&mut bytes).bytes() {
+                        escaped.push_str(&format!("%{:02X}", byte));
+                    }
+                } else {
+                    escaped.push(c);
+                }
+            }
+            return Ok(escaped);
+        },
+        EscapePolicy::Sanitize => {
+            // Trim first, as a path may only begin with `#` or a scheme once trimmed.
+            let trimmed = value.trim();
+            let is_padded = field == UnsafeField::Name || field == UnsafeField::Path;
+            if is_padded && trimmed.len() != value.len() {
+                return escape(trimmed, field, policy);
+            }
+            if is_empty_key {
+                escaped.push('_');
+            }
+            if is_uri {
+                escaped.push_str("./");
+            }
+            for (i, c) in value.char_indices() {
+                match (field, c) {
+                    _ if !is_unsafe(field, value, i, c) => escaped.push(c),
+                    (UnsafeField::AttributeKey, _) => escaped.push('_'),
+                    (UnsafeField::AttributeValue, '"') => escaped.push('\''),
+                    (UnsafeField::Path, '#') => escaped.push_str("./#"),
+                    _ => escaped.push(' '),
+                }
+            }
+            return Ok(escaped);
+        },
+    }
+    let err = UnsafeValueError { field, value: value.into() };
+    Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, err))
+}
+
+/// Whether or not the given character at the given index of the given value of the given field
+/// would not read back as it was written.
+fn is_unsafe(field: UnsafeField, value: &str, index: usize, c: char) -> bool {
+    // Names and paths are trimmed when read.
+    let is_padding = c.is_whitespace()
+        && (value[..index].trim().is_empty() || value[index..].trim().is_empty());
+    match field {
+        _ if c == '\n' || c == '\r' => true,
+        UnsafeField::Name => is_padding,
+        UnsafeField::AttributeKey => c == '=' || c == ',' || c == '"' || c.is_whitespace(),
+        UnsafeField::AttributeValue => c == '"',
+        UnsafeField::Path => (index == 0 && c == '#') || is_padding,
     }
 }
 
+/// Whether or not the given path would be read as a URL or URI rather than as itself.
+fn reads_as_uri(path: &str) -> bool {
+    match read_entry(path) {
+        Entry::Path(ref read) => read.as_os_str() != std::ffi::OsStr::new(path),
+        Entry::Url(_) | Entry::Uri(_) => true,
+    }
+}
+
+/// Format the given absolute path as a `file://` URL.
+pub(crate) fn file_url(path: &PortablePath) -> String {
+    let mut url = "file://".to_string();
+    match path.prefix {
+        None => (),
+        Some(PathPrefix::Drive(letter)) => url.push_str(&format!("/{}:", letter)),
+        Some(PathPrefix::Unc { ref server, ref share }) => {
+            url.push_str(server);
+            url.push('/');
+            url.extend(utf8_percent_encode(share, PATH_SEGMENT_ENCODE_SET));
+        },
+    }
+    for name in &path.components {
+        url.push('/');
+        url.extend(utf8_percent_encode(name, PATH_SEGMENT_ENCODE_SET));
+    }
+    if path.components.is_empty() {
+        url.push('/');
+    }
+    url
+}
 
 impl<W, E> Drop for Writer<W, E>
     where W: Write,
@@ -113,3 +504,23 @@ impl<W, E> Drop for Writer<W, E>
         self.writer.flush().ok();
     }
 }
+
+impl std::error::Error for UnsafeValueError {}
+
+impl std::fmt::Display for UnsafeField {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        let s = match *self {
+            UnsafeField::Name => "name",
+            UnsafeField::AttributeKey => "attribute key",
+            UnsafeField::AttributeValue => "attribute value",
+            UnsafeField::Path => "path",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl std::fmt::Display for UnsafeValueError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "the {} {:?} cannot be written without escaping", self.field, self.value)
+    }
+}
@@ -0,0 +1,434 @@
+//! Reading and writing the XML Shareable Playlist Format (**XSPF**).
+//!
+//! Each `<track>` is read as a `Track`, which may be converted to and from an `EntryExt`:
+//!
+//! - `<location>` maps to the `Entry`. As `<location>` is a URI, absolute `Path` entries are
+//!   written as `file://` URLs and relative `Path` entries as percent-encoded relative URI
+//!   references, e.g. `Greatest%20Hits/Example.ogg`, which are percent-decoded when read.
+//! - `<title>` maps to the `ExtInf` name.
+//! - `<duration>` (in milliseconds) maps to the `ExtInf` duration. A missing duration is read as
+//!   an `Unknown` duration, while `Live` and `Unknown` durations are written without one.
+//! - `<creator>`, `<album>` and `<image>` map to the `ExtInf` attributes of the same name.
+//!
+//! All other elements of a `<track>` or `<playlist>`, such as `<extension>`, `<meta>` or
+//! `<annotation>`, are preserved as `Element`s and written back out unchanged.
+
+use {Duration, Entry, EntryExt, ExtInf, PortablePath};
+use markup;
+use read::read_entry;
+use std;
+use std::io::{Read, Write};
+use url::percent_encoding::{percent_decode, utf8_percent_encode, PATH_SEGMENT_ENCODE_SET};
+use write::file_url;
+use xml;
+
+pub use markup::{Element, Node};
+
+/// The **XSPF** XML namespace.
+pub const NAMESPACE: &str = "http://xspf.org/ns/0/";
+
+pub use CREATOR_ATTRIBUTE;
+/// The `ExtInf` attribute to which `<album>` maps.
+pub const ALBUM_ATTRIBUTE: &str = "album";
+/// The `ExtInf` attribute to which `<image>` maps.
+pub const IMAGE_ATTRIBUTE: &str = "image";
+
+/// A single `<track>` of an **XSPF** playlist.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Track {
+    /// The first `<location>` of the track.
+    pub location: Option<Entry>,
+    /// The `<title>` of the track.
+    pub title: Option<String>,
+    /// The `<creator>` of the track, e.g. the artist.
+    pub creator: Option<String>,
+    /// The `<album>` on which the track appears.
+    pub album: Option<String>,
+    /// The `<duration>` of the track in milliseconds.
+    pub duration_ms: Option<u64>,
+    /// The URI of an `<image>` to display for the track.
+    pub image: Option<String>,
+    /// All other child elements of the track, in order of appearance.
+    pub extensions: Vec<Element>,
+}
+
+/// Information about an **XSPF** playlist as a whole.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// The `<title>` of the playlist.
+    pub title: Option<String>,
+    /// The `<creator>` of the playlist.
+    pub creator: Option<String>,
+    /// All other child elements of the playlist except for the `<trackList>`, in order of
+    /// appearance.
+    pub extensions: Vec<Element>,
+}
+
+/// A reader that reads the **XSPF** format.
+///
+/// The whole document is read upon construction.
+pub struct Reader {
+    /// Information about the playlist as a whole.
+    metadata: Metadata,
+    /// The tracks that have not yet been yielded.
+    tracks: std::vec::IntoIter<Track>,
+}
+
+/// A writer that writes the **XSPF** format.
+///
+/// Everything up to and including the opening `<trackList>` tag is written upon construction and
+/// the closing tags are written by `finish`. If `finish` is not called, the closing tags are
+/// written when the writer is dropped, though any errors that occur in the process cannot be
+/// handled.
+pub struct Writer<W>
+    where W: Write,
+{
+    /// The writer to which the **XSPF** format is written.
+    writer: W,
+    /// Whether or not the closing tags have been written.
+    finished: bool,
+}
+
+/// An iterator that yields the `Track`s of an **XSPF** playlist.
+pub struct Tracks<'r> {
+    reader: &'r mut Reader,
+}
+
+/// An iterator that yields the `EntryExt`s of an **XSPF** playlist.
+///
+/// Tracks without a `<location>` are skipped.
+pub struct EntryExts<'r> {
+    reader: &'r mut Reader,
+}
+
+/// Errors that may occur when reading the **XSPF** format.
+#[derive(Debug)]
+pub enum Error {
+    /// The document was not well-formed XML.
+    Xml(xml::reader::Error),
+    /// The root element of the document was not a `<playlist>`.
+    PlaylistNotFound,
+    /// The contents of a `<duration>` element was not a number of milliseconds.
+    InvalidDuration(String),
+    /// Errors produced when opening a file.
+    Io(std::io::Error),
+}
+
+
+impl Reader {
+
+    /// Read an **XSPF** playlist from the given reader.
+    pub fn new<R>(reader: R) -> Result<Self, Error>
+        where R: Read,
+    {
+        let root = match markup::parse(reader)? {
+            Some(root) if root.local_name() == "playlist" => root,
+            _ => return Err(Error::PlaylistNotFound),
+        };
+
+        // Namespaces in scope that must be carried along with preserved elements.
+        let mut scope = foreign_namespaces(&root.namespaces);
+
+        let mut metadata = Metadata::default();
+        let mut tracks = Vec::new();
+        for element in root.elements() {
+            match element.local_name() {
+                "title" => metadata.title = Some(element.text()),
+                "creator" => metadata.creator = Some(element.text()),
+                "trackList" => {
+                    scope.extend(foreign_namespaces(&element.namespaces));
+                    for track in element.elements().filter(|e| e.local_name() == "track") {
+                        let mut scope = scope.clone();
+                        scope.extend(foreign_namespaces(&track.namespaces));
+                        tracks.push(read_track(track, &scope)?);
+                    }
+                },
+                _ => metadata.extensions.push(markup::detach(element, &scope)),
+            }
+        }
+
+        Ok(Reader { metadata, tracks: tracks.into_iter() })
+    }
+
+    /// Attempts to read an **XSPF** playlist from the specified file.
+    ///
+    /// This is a convenience constructor that opens a `File`, wraps it in a `BufReader` and then
+    /// constructs a `Reader` from it.
+    pub fn open<P>(filename: P) -> Result<Self, Error>
+        where P: AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(filename)?;
+        let buf_reader = std::io::BufReader::new(file);
+        Self::new(buf_reader)
+    }
+
+    /// Information about the playlist as a whole.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    /// Produce an iterator that yields `Track`s.
+    pub fn tracks(&mut self) -> Tracks<'_> {
+        Tracks { reader: self }
+    }
+
+    /// Produce an iterator that yields `EntryExt`s, skipping tracks without a `<location>`.
+    pub fn entry_exts(&mut self) -> EntryExts<'_> {
+        EntryExts { reader: self }
+    }
+
+}
+
+impl<W> Writer<W>
+    where W: Write,
+{
+
+    /// Create a writer that writes an **XSPF** playlist without a title or creator.
+    pub fn new(writer: W) -> Result<Self, std::io::Error> {
+        Self::with_metadata(writer, &Metadata::default())
+    }
+
+    /// Create a writer that writes an **XSPF** playlist with the given metadata.
+    ///
+    /// Everything up to and including the opening `<trackList>` tag is written immediately.
+    pub fn with_metadata(mut writer: W, metadata: &Metadata) -> Result<Self, std::io::Error> {
+        markup::write_declaration(&mut writer)?;
+        let mut playlist = Element::new("playlist");
+        playlist.namespaces.push((String::new(), NAMESPACE.to_string()));
+        playlist.attributes.push(("version".to_string(), "1".to_string()));
+        markup::write_start(&mut writer, &playlist)?;
+        writeln!(writer)?;
+
+        if let Some(ref title) = metadata.title {
+            markup::write_element(&mut writer, &Element::with_text("title", &title[..]), 1)?;
+        }
+        if let Some(ref creator) = metadata.creator {
+            markup::write_element(&mut writer, &Element::with_text("creator", &creator[..]), 1)?;
+        }
+        for element in &metadata.extensions {
+            markup::write_element(&mut writer, element, 1)?;
+        }
+
+        writeln!(writer, "  <trackList>")?;
+        Ok(Writer { writer, finished: false })
+    }
+
+    /// Attempt to write the given `Track`.
+    pub fn write_track(&mut self, track: &Track) -> Result<(), std::io::Error> {
+        markup::write_element(&mut self.writer, &track_element(track), 2)
+    }
+
+    /// Attempt to write the given `EntryExt` as a `Track`.
+    pub fn write_entry_ext(&mut self, entry_ext: &EntryExt) -> Result<(), std::io::Error> {
+        self.write_track(&Track::from(entry_ext))
+    }
+
+    /// Write the closing `</trackList>` and `</playlist>` tags and flush the inner writer.
+    pub fn finish(mut self) -> Result<(), std::io::Error> {
+        self.finished = true;
+        write_footer(&mut self.writer)?;
+        self.writer.flush()
+    }
+
+}
+
+impl Track {
+
+    /// Convert the track into an `EntryExt`.
+    ///
+    /// Returns `None` if the track has no `location`.
+    pub fn into_entry_ext(self) -> Option<EntryExt> {
+        let Track { location, title, creator, album, duration_ms, image, .. } = self;
+        let entry = location?;
+        let duration = duration_ms.map(std::time::Duration::from_millis).map(Duration::Known);
+        let duration = duration.unwrap_or(Duration::Unknown);
+        let mut extinf = ExtInf::new(duration, title.unwrap_or_default());
+        let attributes = vec![
+            (CREATOR_ATTRIBUTE, creator),
+            (ALBUM_ATTRIBUTE, album),
+            (IMAGE_ATTRIBUTE, image),
+        ];
+        for (key, value) in attributes {
+            if let Some(value) = value {
+                extinf.set_attribute(key, value);
+            }
+        }
+        Some(EntryExt { entry, extinf })
+    }
+
+}
+
+
+/// Read a `Track` from the given `<track>` element.
+fn read_track(element: &Element, scope: &[(String, String)]) -> Result<Track, Error> {
+    let mut track = Track::default();
+    for child in element.elements() {
+        match child.local_name() {
+            "location" if track.location.is_none() =>
+                track.location = Some(read_location(&child.text())),
+            "title" => track.title = Some(child.text()),
+            "creator" => track.creator = Some(child.text()),
+            "album" => track.album = Some(child.text()),
+            "image" => track.image = Some(child.text()),
+            "duration" => {
+                let text = child.text();
+                match text.parse() {
+                    Ok(ms) => track.duration_ms = Some(ms),
+                    Err(_) => return Err(Error::InvalidDuration(text)),
+                }
+            },
+            _ => track.extensions.push(markup::detach(child, scope)),
+        }
+    }
+    Ok(track)
+}
+
+/// Read the `Entry` of the given `<location>`, percent-decoding relative URI references.
+fn read_location(location: &str) -> Entry {
+    match read_entry(location) {
+        Entry::Path(ref path) if path.as_os_str() == std::ffi::OsStr::new(location) => {
+            Entry::Path(percent_decode(location.as_bytes()).decode_utf8_lossy().as_ref().into())
+        },
+        entry => entry,
+    }
+}
+
+/// The `<location>` URI of the given path: a `file://` URL if the path is absolute, or a
+/// percent-encoded relative URI reference otherwise.
+fn path_location(path: &std::path::Path) -> String {
+    let path = PortablePath::parse(&path.to_string_lossy());
+    if path.has_root || path.prefix.is_some() {
+        return file_url(&path);
+    }
+    let segments: Vec<String> = path.components.iter()
+        .map(|name| utf8_percent_encode(name, PATH_SEGMENT_ENCODE_SET).collect())
+        .collect();
+    let location = segments.join("/");
+    // A `:` within the first segment would otherwise be read as the end of a scheme.
+    match segments.first() {
+        Some(first) if first.contains(':') => format!("./{}", location),
+        _ => location,
+    }
+}
+
+/// Produce the `<track>` element for the given `Track`.
+fn track_element(track: &Track) -> Element {
+    let mut element = Element::new("track");
+    match track.location {
+        Some(Entry::Path(ref path)) =>
+            element.push_text_element("location", path_location(path)),
+        Some(Entry::Url(ref url)) | Some(Entry::Uri(ref url)) =>
+            element.push_text_element("location", url.as_str()),
+        None => (),
+    }
+    let fields = [("title", &track.title), ("creator", &track.creator), ("album", &track.album)];
+    for &(name, value) in fields.iter() {
+        if let Some(ref value) = *value {
+            element.push_text_element(name, &value[..]);
+        }
+    }
+    if let Some(ms) = track.duration_ms {
+        element.push_text_element("duration", ms.to_string());
+    }
+    if let Some(ref image) = track.image {
+        element.push_text_element("image", &image[..]);
+    }
+    element.children.extend(track.extensions.iter().cloned().map(Node::Element));
+    element
+}
+
+/// Write the closing `</trackList>` and `</playlist>` tags.
+fn write_footer<W>(writer: &mut W) -> Result<(), std::io::Error>
+    where W: Write,
+{
+    writeln!(writer, "  </trackList>")?;
+    writeln!(writer, "</playlist>")
+}
+
+/// The given namespace declarations, excluding the default **XSPF** namespace.
+fn foreign_namespaces(namespaces: &[(String, String)]) -> Vec<(String, String)> {
+    namespaces.iter()
+        .filter(|&(prefix, uri)| !(prefix.is_empty() && uri == NAMESPACE))
+        .cloned()
+        .collect()
+}
+
+
+impl<'a> From<&'a EntryExt> for Track {
+    fn from(entry_ext: &'a EntryExt) -> Self {
+        let extinf = &entry_ext.extinf;
+        let attribute = |key| extinf.attribute(key).map(str::to_string);
+        let duration_ms = extinf.duration.known().map(|duration| duration.as_millis() as u64);
+        Track {
+            location: Some(entry_ext.entry.clone()),
+            title: if extinf.name.is_empty() { None } else { Some(extinf.name.clone()) },
+            creator: attribute(CREATOR_ATTRIBUTE),
+            album: attribute(ALBUM_ATTRIBUTE),
+            duration_ms,
+            image: attribute(IMAGE_ATTRIBUTE),
+            extensions: Vec::new(),
+        }
+    }
+}
+
+
+impl<'r> Iterator for Tracks<'r> {
+    type Item = Track;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.tracks.next()
+    }
+}
+
+impl<'r> Iterator for EntryExts<'r> {
+    type Item = EntryExt;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.reader.tracks.by_ref().filter_map(Track::into_entry_ext).next()
+    }
+}
+
+
+impl<W> Drop for Writer<W>
+    where W: Write,
+{
+    fn drop(&mut self) {
+        if !self.finished {
+            write_footer(&mut self.writer).ok();
+            self.writer.flush().ok();
+        }
+    }
+}
+
+
+impl From<xml::reader::Error> for Error {
+    fn from(err: xml::reader::Error) -> Self {
+        Error::Xml(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match *self {
+            Error::Xml(ref err) => Some(err),
+            Error::Io(ref err) => Some(err),
+            Error::PlaylistNotFound | Error::InvalidDuration(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Error::Xml(ref err) => err.fmt(f),
+            Error::PlaylistNotFound => write!(f, "the root `<playlist>` element was not found"),
+            Error::InvalidDuration(ref text) =>
+                write!(f, "`{}` is not a valid duration in milliseconds", text),
+            Error::Io(ref err) => err.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,18 @@
+REM GENRE Rock
+REM DATE 1999
+PERFORMER "Example Artist"
+TITLE "Greatest Hits"
+FILE "Greatest Hits.flac" WAVE
+  TRACK 01 AUDIO
+    TITLE "Example title"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Sample title"
+    PERFORMER "Sample Artist"
+    ISRC USABC9900001
+    INDEX 00 05:19:00
+    INDEX 01 05:21:00
+FILE "Bonus.mp3" MP3
+  TRACK 03 AUDIO
+    TITLE "Bonus"
+    INDEX 01 00:00:00
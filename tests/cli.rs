@@ -0,0 +1,47 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn m3u(args: &[&str], stdin: &[u8]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_m3u"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(stdin).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn convert_and_fmt() {
+    let output = m3u(&["convert", "--to", "pls", "tests/ext.m3u"], b"");
+    assert!(output.status.success());
+    let pls = output.stdout;
+    assert!(pls.starts_with(b"[playlist]\n"));
+
+    // Converting back via stdin and formatting produces the canonical extended M3U.
+    let output = m3u(&["convert", "--to", "m3u-ext"], &pls);
+    let canonical = m3u(&["fmt", "tests/ext.m3u"], b"").stdout;
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), String::from_utf8(canonical).unwrap());
+}
+
+#[test]
+fn validate_and_stats() {
+    let output = m3u(&["validate", "tests/ext.m3u", "-"], b"\0binary");
+    assert_eq!(output.status.code(), Some(1));
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert!(report.contains("tests/ext.m3u: ok (m3u-ext, 4 entries)"), "{}", report);
+    assert!(report.contains("<stdin>: error:"), "{}", report);
+
+    // A leading byte order mark is skipped.
+    let output = m3u(&["validate", "-"], b"\xef\xbb\xbf#EXTM3U\n#EXTINF:1,A\nhttp://host/a.mp3\n");
+    assert_eq!(output.status.code(), Some(0));
+    let report = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(report, "<stdin>: ok (m3u-ext, 1 entries)\n");
+
+    let output = m3u(&["stats", "tests/mixed.m3u"], b"");
+    let stats = String::from_utf8(output.stdout).unwrap();
+    assert!(stats.contains("entries: 7\npaths: 5\nurls: 2\n"), "{}", stats);
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "csv")]
+
+extern crate m3u;
+
+#[test]
+fn round_trip() {
+    let mut news = m3u::url_entry("http://www.example.com/news.m3u8").unwrap()
+        .extend(-1.0, "News, Weather & Sport");
+    news.extinf.set_attribute("group-title", "News");
+    let playlist = vec![
+        m3u::path_entry(r"Greatest Hits\Example.ogg")
+            .extend(321.0, "Example Artist - Example title"),
+        news,
+    ];
+
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::csv::Writer::with_attributes(&mut bytes, vec!["group-title"]).unwrap();
+        for entry in &playlist {
+            writer.write_entry(entry).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+    let csv = String::from_utf8(bytes).unwrap();
+    assert_eq!(csv, "location,kind,duration,name,group-title\n\
+                     Greatest Hits\\Example.ogg,path,321,Example Artist - Example title,\n\
+                     http://www.example.com/news.m3u8,url,-1,\"News, Weather & Sport\",News\n");
+
+    let mut reader = m3u::csv::Reader::new(csv.as_bytes()).unwrap();
+    let read: Vec<_> = reader.entry_exts().map(|e| e.unwrap()).collect();
+    assert_eq!(playlist, read);
+}
+
+#[test]
+fn invalid() {
+    assert!(m3u::csv::Reader::new(&b"location,kind,name\n"[..]).is_err());
+    let csv = "name,kind,location,duration\nFoo,file,foo.mp3,1\n";
+    let mut reader = m3u::csv::Reader::new(csv.as_bytes()).unwrap();
+    match reader.entry_exts().next() {
+        Some(Err(m3u::csv::Error::InvalidKind(ref kind))) => assert_eq!(kind, "file"),
+        _ => panic!("expected an invalid kind error"),
+    }
+}
@@ -0,0 +1,85 @@
+extern crate m3u;
+
+use m3u::cue::{CueSheet, Time};
+
+#[test]
+fn read() {
+    let sheet = CueSheet::open("tests/album.cue").unwrap();
+    assert_eq!(sheet.rem, vec![("GENRE".to_string(), "Rock".to_string()),
+                               ("DATE".to_string(), "1999".to_string())]);
+    assert_eq!(sheet.title.as_ref().map(|s| &s[..]), Some("Greatest Hits"));
+    assert_eq!(sheet.files.len(), 2);
+    let track = &sheet.files[0].tracks[1];
+    assert_eq!(track.other, vec!["ISRC USABC9900001".to_string()]);
+    assert_eq!(track.start(), Time::new(5, 21, 0));
+
+    assert!(CueSheet::read(&b"TRACK 01 AUDIO\n"[..]).is_err());
+    assert!(CueSheet::read(&b"FILE a.wav WAVE\n  INDEX 01 00:00:00\n"[..]).is_err());
+    let overflow = b"FILE a.wav WAVE\n  TRACK 01 AUDIO\n    INDEX 01 999999999999999999:00:00\n";
+    match CueSheet::read(&overflow[..]) {
+        Err(m3u::cue::Error::InvalidLine(3)) => (),
+        other => panic!("expected an invalid line 3, found {:?}", other),
+    }
+    assert_eq!(Time::checked_new(u64::MAX, 0, 0), None);
+
+    // Reading back what was written should produce the same sheet.
+    let mut bytes = Vec::new();
+    sheet.write(&mut bytes).unwrap();
+    assert_eq!(CueSheet::read(&bytes[..]).unwrap(), sheet);
+}
+
+#[test]
+fn entry_exts() {
+    let sheet = CueSheet::open("tests/album.cue").unwrap();
+    let entries = sheet.to_entry_exts();
+    let expected = vec![
+        m3u::path_entry("Greatest Hits.flac").extend(321.0, "Example title"),
+        m3u::path_entry("Greatest Hits.flac").extend(m3u::Duration::Unknown, "Sample title"),
+        m3u::path_entry("Bonus.mp3").extend(m3u::Duration::Unknown, "Bonus"),
+    ];
+    for (entry, expected) in entries.iter().zip(&expected) {
+        assert_eq!(entry.entry, expected.entry);
+        assert_eq!(entry.extinf.duration, expected.extinf.duration);
+        assert_eq!(entry.extinf.name, expected.extinf.name);
+    }
+    assert_eq!(m3u::cue::offsets(&entries[0].extinf), Some((0.0, Some(321.0))));
+    assert_eq!(m3u::cue::offsets(&entries[1].extinf), Some((321.0, None)));
+
+    // Tracks out of order have an unknown duration rather than a negative one.
+    let unordered = b"FILE a.wav WAVE\n  TRACK 01 AUDIO\n    INDEX 01 02:00:00\n\
+                      TRACK 02 AUDIO\n    INDEX 01 01:00:00\n";
+    let unordered = CueSheet::read(&unordered[..]).unwrap().to_entry_exts();
+    assert_eq!(unordered[0].extinf.duration, m3u::Duration::Unknown);
+    assert_eq!(entries[0].extinf.attribute("creator"), Some("Example Artist"));
+    assert_eq!(entries[1].extinf.attribute("creator"), Some("Sample Artist"));
+
+    // Converting back groups the tracks by file and restores their offsets.
+    let back = CueSheet::from_entry_exts(&entries);
+    assert_eq!(back.files.len(), 2);
+    assert_eq!(back.files[1].kind, "MP3");
+    assert_eq!(back.files[0].tracks[1].start(), Time::new(5, 21, 0));
+    assert_eq!(back.to_entry_exts(), entries);
+}
+
+#[test]
+fn gaps_appended() {
+    // EAC's "gaps appended" sheets place each track's `INDEX 01` in the following file.
+    let sheet = CueSheet::open("tests/gaps_appended.cue").unwrap();
+    assert_eq!(sheet.files.len(), 3);
+    let second = &sheet.files[0].tracks[1];
+    assert_eq!(second.start(), Time::new(4, 10, 50));
+    assert_eq!(second.continued_indices[0].time, Time::new(0, 0, 0));
+
+    let entries = sheet.to_entry_exts();
+    let paths: Vec<_> = entries.iter().map(|e| e.entry.clone()).collect();
+    assert_eq!(paths, vec![m3u::path_entry("01 First.wav"),
+                           m3u::path_entry("02 Second.wav"),
+                           m3u::path_entry("03 Third.wav")]);
+    assert_eq!(m3u::cue::offsets(&entries[0].extinf), Some((0.0, Some(250.0 + 50.0 / 75.0))));
+    assert_eq!(m3u::cue::offsets(&entries[1].extinf), Some((0.0, Some(200.0))));
+    assert_eq!(m3u::cue::offsets(&entries[2].extinf), Some((0.0, None)));
+
+    let mut bytes = Vec::new();
+    sheet.write(&mut bytes).unwrap();
+    assert_eq!(CueSheet::read(&bytes[..]).unwrap(), sheet);
+}
@@ -0,0 +1,61 @@
+extern crate m3u;
+
+use m3u::Format;
+
+#[test]
+fn detect_format() {
+    let detect = |s: &str| m3u::detect_format(s.as_bytes());
+    assert_eq!(detect("foo.mp3\nbar.mp3\n"), Some(Format::M3u));
+    assert_eq!(detect("\u{feff}#EXTM3U\n#EXTINF:1,Foo\nfoo.mp3\n"), Some(Format::M3uExt));
+    assert_eq!(detect("#EXTM3U\n#EXT-X-TARGETDURATION:10\n#EXTINF:9.9,\nseg0.ts\n"),
+               Some(Format::HlsMedia));
+    assert_eq!(detect("#EXTM3U\n#EXT-X-STREAM-INF:BANDWIDTH=1280000\nlow.m3u8\n"),
+               Some(Format::HlsMaster));
+    assert_eq!(detect("[Playlist]\nFile1=foo.mp3\n"), Some(Format::Pls));
+    assert_eq!(detect("<?xml version=\"1.0\"?>\n<!-- x -->\n<playlist version=\"1\">"),
+               Some(Format::Xspf));
+    assert_eq!(detect("<ASX version=\"3.0\">"), Some(Format::Asx));
+    assert_eq!(detect("<?wpl version=\"1.0\"?>\n<smil>"), Some(Format::Wpl));
+    assert_eq!(detect("REM DATE 1999\nFILE \"a.wav\" WAVE\n  TRACK 01 AUDIO\n"), Some(Format::Cue));
+    assert_eq!(detect("FILE one.mp3\nTRACK two.mp3\n"), Some(Format::M3u));
+    assert_eq!(detect("<html>"), None);
+    assert_eq!(m3u::detect_format(b"ID3\0\0"), None);
+}
+
+#[test]
+fn open_any() {
+    let mut formats = vec![
+        ("tests/mixed.m3u", Format::M3u),
+        ("tests/ext.m3u", Format::M3uExt),
+        ("tests/stream.pls", Format::Pls),
+        ("tests/album.cue", Format::Cue),
+    ];
+    if cfg!(feature = "xml") {
+        formats.push(("tests/example.xspf", Format::Xspf));
+        formats.push(("tests/example.asx", Format::Asx));
+        formats.push(("tests/example.wpl", Format::Wpl));
+    } else {
+        let err = m3u::open_any("tests/example.xspf").unwrap_err();
+        assert!(matches!(err, m3u::OpenError::UnsupportedFormat(Format::Xspf)));
+    }
+    for &(path, format) in formats.iter() {
+        let playlist = m3u::open_any(path).unwrap();
+        assert_eq!(playlist.format, format);
+        assert!(!playlist.entries.is_empty());
+    }
+
+    let playlist = m3u::open_any("tests/mixed.m3u").unwrap();
+    let expected = m3u::Reader::open("tests/mixed.m3u").unwrap()
+        .entries()
+        .map(|e| e.unwrap().extend(m3u::Duration::Unknown, ""))
+        .collect::<Vec<_>>();
+    assert_eq!(playlist.entries, expected);
+
+    // A leading byte order mark is not part of the first line.
+    let playlist = m3u::read_any(&b"\xEF\xBB\xBF#EXTM3U\n#EXTINF:1,Foo\nfoo.mp3\n"[..]).unwrap();
+    assert_eq!(playlist.entries, vec![m3u::path_entry("foo.mp3").extend(1.0, "Foo")]);
+    let playlist = m3u::read_any(&b"\xEF\xBB\xBFfoo.mp3\n"[..]).unwrap();
+    assert_eq!(playlist.entries[0].entry, m3u::path_entry("foo.mp3"));
+    assert_eq!(m3u::open_any("tests/album.cue").unwrap().title.as_ref().map(|s| &s[..]),
+               Some("Greatest Hits"));
+}
@@ -0,0 +1,74 @@
+extern crate m3u;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn playlist() -> Vec<m3u::EntryExt> {
+    vec![
+        m3u::path_entry(r"Sample.mp3").extend(123.0, "Sample artist - Sample title"),
+        m3u::path_entry(r"Greatest Hits\Example.ogg").extend(321.0, "Example Artist - Example title"),
+        m3u::url_entry(r"http://www.example.com/~user/Mine.mp3").unwrap().extend(-1.0, "Mine"),
+    ]
+}
+
+#[test]
+fn undo_redo() {
+    let original = playlist();
+    let mut editor = m3u::Editor::new(original.clone());
+
+    editor.move_entry(0, 2).unwrap();
+    editor.remove(0).unwrap();
+    let extinf = m3u::ExtInf::new(60.0, "Renamed");
+    editor.set_extinf(0, extinf.clone()).unwrap();
+    assert_eq!(editor.entries().len(), 2);
+    assert_eq!(editor.entries()[0].extinf, extinf);
+    assert_eq!(editor.entries()[1], original[0]);
+
+    // Undo everything.
+    while editor.undo() {}
+    assert_eq!(editor.entries(), &original[..]);
+    assert!(!editor.can_undo());
+
+    // Redo the move only, then make a new edit which should clear the redo stack.
+    assert!(editor.redo());
+    assert_eq!(editor.entries()[2], original[0]);
+    editor.push(m3u::path_entry("Stuff.mp3").extend(10.0, "Stuff"));
+    assert!(!editor.can_redo());
+    assert_eq!(editor.entries().len(), 4);
+
+    assert_eq!(editor.remove(4), Err(m3u::EditError::IndexOutOfBounds { index: 4, len: 4 }));
+}
+
+#[test]
+fn batch_and_changes() {
+    let original = playlist();
+    let mut editor = m3u::Editor::new(original.clone());
+
+    let changes = Rc::new(RefCell::new(Vec::new()));
+    {
+        let changes = changes.clone();
+        editor.on_change(move |change| changes.borrow_mut().push(*change));
+    }
+
+    editor.batch(|editor| {
+        editor.remove(2)?;
+        editor.remove(0)
+    }).unwrap();
+    assert_eq!(editor.entries(), &original[1..2]);
+    assert_eq!(&*changes.borrow(), &[m3u::Change::Removed(2), m3u::Change::Removed(0)]);
+
+    // A single undo reverts the whole batch, in reverse order.
+    changes.borrow_mut().clear();
+    assert!(editor.undo());
+    assert_eq!(editor.entries(), &original[..]);
+    assert_eq!(&*changes.borrow(), &[m3u::Change::Inserted(0), m3u::Change::Inserted(2)]);
+
+    // A failing batch is rolled back and leaves no history.
+    assert!(editor.batch(|editor| {
+        editor.remove(0)?;
+        editor.remove(10)
+    }).is_err());
+    assert_eq!(editor.entries(), &original[..]);
+    assert!(!editor.can_undo());
+    assert!(editor.can_redo());
+}
@@ -0,0 +1,42 @@
+extern crate m3u;
+
+use m3u::Expander;
+
+#[test]
+fn expand_directories() {
+    let dir = std::env::temp_dir().join(format!("m3u-expand-{}", std::process::id()));
+    let files = [
+        "Track 10.mp3", "track 2.MP3", "Track 1.flac", "cover.jpg", ".hidden.mp3",
+        "Disc 2/01.ogg", "Disc 10/01.ogg", ".cache/x.mp3",
+    ];
+    for file in &files {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"").unwrap();
+    }
+    let names = |paths: Vec<std::path::PathBuf>| -> Vec<String> {
+        paths.iter()
+            .map(|p| p.strip_prefix(&dir).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect()
+    };
+
+    // Dangling symbolic links are skipped rather than failing the expansion.
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(dir.join("missing"), dir.join("dangling.mp3")).unwrap();
+
+    let expanded = Expander::new().expand_dir(&dir).unwrap();
+    assert_eq!(names(expanded), vec![
+        "Disc 2/01.ogg", "Disc 10/01.ogg", "Track 1.flac", "track 2.MP3", "Track 10.mp3",
+    ]);
+
+    let flat = Expander::new().recursive(false).extensions(&["mp3"]).include_hidden(true);
+    assert_eq!(names(flat.expand_dir(&dir).unwrap()),
+               vec![".hidden.mp3", "track 2.MP3", "Track 10.mp3"]);
+
+    let url = m3u::url_entry("http://example.com/a.mp3").unwrap();
+    let entries = vec![m3u::path_entry(dir.join("Disc 2")), url.clone()];
+    let expanded = Expander::new().expand_entries(entries).unwrap();
+    assert_eq!(expanded, vec![m3u::path_entry(dir.join("Disc 2").join("01.ogg")), url]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,79 @@
+extern crate m3u;
+
+use m3u::{Entry, FlattenErrorKind, Flattener, Url};
+use std::path::PathBuf;
+
+/// Write the given playlists into a fresh temporary directory.
+fn playlists(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("m3u-flatten-{}-{}", name, std::process::id()));
+    for &(path, content) in files {
+        let path = dir.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+    dir
+}
+
+#[test]
+fn flatten_nested() {
+    let dir = playlists("nested", &[
+        ("root.m3u", "a.mp3\nsub/nested.m3u\nhttp://example.com/remote.m3u\nc.mp3\n"),
+        ("sub/nested.m3u", "#EXTM3U\n#EXTINF:10,B\nb.mp3\n../root.m3u\n"),
+    ]);
+    let results: Vec<_> = Flattener::new().open(dir.join("root.m3u")).collect();
+    assert_eq!(results.len(), 5);
+
+    let root = m3u::path_entry(dir.join("root.m3u"));
+    let nested = m3u::path_entry(dir.join("sub/nested.m3u"));
+    let flat = results[1].as_ref().unwrap();
+    assert_eq!(flat.entry_ext.entry, m3u::path_entry(dir.join("sub/b.mp3")));
+    assert_eq!(flat.entry_ext.extinf.name, "B");
+    assert_eq!(flat.provenance, vec![root.clone(), nested.clone()]);
+
+    let err = results[2].as_ref().unwrap_err();
+    match err.kind {
+        FlattenErrorKind::Cycle => (),
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    assert_eq!(err.provenance, vec![root.clone(), nested]);
+
+    // Without a fetcher, URL entries are yielded as they are.
+    let url = results[3].as_ref().unwrap();
+    assert_eq!(url.entry_ext.entry, m3u::url_entry("http://example.com/remote.m3u").unwrap());
+    assert_eq!(url.provenance, vec![root]);
+    assert_eq!(results[4].as_ref().unwrap().entry_ext.entry, m3u::path_entry(dir.join("c.mp3")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn flatten_depth_and_urls() {
+    let dir = playlists("depth", &[
+        ("one.m3u", "two.m3u\n"),
+        ("two.m3u", "three.pls\n"),
+        ("three.pls", "[playlist]\nFile1=three.mp3\nNumberOfEntries=1\nVersion=2\n"),
+    ]);
+    let flat: Vec<_> = Flattener::new().open(dir.join("one.m3u")).collect();
+    assert_eq!(flat[0].as_ref().unwrap().entry_ext.entry, m3u::path_entry(dir.join("three.mp3")));
+    let shallow: Vec<_> = Flattener::new().max_depth(1).open(dir.join("one.m3u")).collect();
+    match shallow[0].as_ref().unwrap_err().kind {
+        FlattenErrorKind::DepthExceeded => (),
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let fetch = |url: &Url| match url.path() {
+        "/lists/remote.m3u" => Ok(b"track.mp3\n/other.m3u\n".to_vec()),
+        _ => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "not found")),
+    };
+    let remote = m3u::url_entry("http://example.com/lists/remote.m3u").unwrap();
+    let results: Vec<_> = Flattener::new().fetcher(&fetch).flatten(remote).collect();
+    let track = Url::parse("http://example.com/lists/track.mp3").unwrap();
+    assert_eq!(results[0].as_ref().unwrap().entry_ext.entry, Entry::Url(track));
+    let err = results[1].as_ref().unwrap_err();
+    assert_eq!(err.playlist, m3u::url_entry("http://example.com/other.m3u").unwrap());
+    match err.kind {
+        FlattenErrorKind::Open(m3u::OpenError::Io(_)) => (),
+        ref kind => panic!("unexpected error kind: {:?}", kind),
+    }
+}
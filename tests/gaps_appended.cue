@@ -0,0 +1,18 @@
+REM GENRE Rock
+REM COMMENT "ExactAudioCopy v1.6"
+PERFORMER "Example Artist"
+TITLE "Gaps Appended"
+FILE "01 First.wav" WAVE
+  TRACK 01 AUDIO
+    TITLE "First"
+    INDEX 01 00:00:00
+  TRACK 02 AUDIO
+    TITLE "Second"
+    INDEX 00 04:10:50
+FILE "02 Second.wav" WAVE
+    INDEX 01 00:00:00
+  TRACK 03 AUDIO
+    TITLE "Third"
+    INDEX 00 03:20:00
+FILE "03 Third.wav" WAVE
+    INDEX 01 00:00:00
@@ -0,0 +1,80 @@
+extern crate m3u;
+
+use m3u::{Generator, Grouping};
+
+#[test]
+fn generate_playlists() {
+    let dir = std::env::temp_dir().join(format!("m3u-generate-{}", std::process::id()));
+    let files = ["Album/2 Two.mp3", "Album/10 Ten.mp3", "Album/cover.jpg", "Album/CD 2/1 One.flac"];
+    for file in &files {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"").unwrap();
+    }
+
+    let paths = Generator::new().generate_files(&dir).unwrap();
+    assert_eq!(paths, vec![dir.join("Album/Album.m3u"), dir.join("Album/CD 2/CD 2.m3u")]);
+    let album = std::fs::read_to_string(&paths[0]).unwrap();
+    assert_eq!(album, "2 Two.mp3\n10 Ten.mp3\n");
+
+    let single = Generator::new().grouping(Grouping::Single).extended(true);
+    let playlists = single.generate(&dir).unwrap();
+    assert_eq!(playlists.len(), 1);
+    let mut written = Vec::new();
+    playlists[0].write(&mut written).unwrap();
+    assert_eq!(String::from_utf8(written).unwrap(), "#EXTM3U\n\
+                                                     #EXTINF:-1,2 Two\n\
+                                                     Album/2 Two.mp3\n\
+                                                     #EXTINF:-1,10 Ten\n\
+                                                     Album/10 Ten.mp3\n\
+                                                     #EXTINF:-1,1 One\n\
+                                                     Album/CD 2/1 One.flac\n");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn generate_unsafe_names() {
+    let dir = std::env::temp_dir().join(format!("m3u-generate-unsafe-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("Hits")).unwrap();
+    std::fs::write(dir.join("Hits/#1 Crush.mp3"), b"").unwrap();
+
+    // File names that would be read as comments are still written, preceded by `./`.
+    let paths = Generator::new().extended(true).generate_files(&dir).unwrap();
+    assert_eq!(paths, vec![dir.join("Hits/Hits.m3u")]);
+    let hits = std::fs::read_to_string(&paths[0]).unwrap();
+    assert_eq!(hits, "#EXTM3U\n#EXTINF:-1,#1 Crush\n./#1 Crush.mp3\n");
+    let mut reader = m3u::Reader::open_ext(&paths[0]).unwrap();
+    let entry = reader.entry_exts().next().unwrap().unwrap().entry;
+    assert_eq!(entry.resolve(&m3u::Base::from_playlist_path(&paths[0])),
+               m3u::path_entry(dir.join("Hits/#1 Crush.mp3")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+#[cfg(unix)]
+fn generate_skips_unreadable_directories() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = std::env::temp_dir().join(format!("m3u-generate-skip-{}", std::process::id()));
+    for file in &["Album/1 One.mp3", "Locked/1 One.mp3"] {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, b"").unwrap();
+    }
+    std::os::unix::fs::symlink(dir.join("missing"), dir.join("Dangling")).unwrap();
+    let locked = dir.join("Locked");
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o000)).unwrap();
+
+    // Unreadable subdirectories and dangling links are skipped rather than failing the walk.
+    let playlists = Generator::new().generate(&dir);
+    std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let paths: Vec<_> = playlists.unwrap().into_iter().map(|p| p.path).collect();
+    assert_eq!(paths[0], dir.join("Album/Album.m3u"));
+    assert!(paths.iter().all(|path| !path.starts_with(dir.join("Dangling"))));
+
+    assert!(Generator::new().generate(dir.join("missing")).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "json")]
+
+extern crate m3u;
+
+fn playlist() -> Vec<m3u::EntryExt> {
+    let mut news = m3u::url_entry("http://www.example.com/news.m3u8").unwrap()
+        .extend(-1.0, "News Channel");
+    news.extinf.set_attribute("group-title", "News");
+    vec![
+        m3u::path_entry(r"C:\Documents and Settings\I\My Music\Sample.mp3")
+            .extend(123.0, "Sample artist - Sample title"),
+        news,
+    ]
+}
+
+#[test]
+fn array() {
+    let playlist = playlist();
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::json::Writer::new(&mut bytes).unwrap();
+        for entry in &playlist {
+            writer.write_entry(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    let mut reader = m3u::json::Reader::new(&bytes[..]).unwrap();
+    let read: Vec<_> = reader.entry_exts().map(|e| e.unwrap()).collect();
+    assert_eq!(playlist, read);
+
+    let mut reader = m3u::json::Reader::new(&b" [ ] "[..]).unwrap();
+    assert!(reader.entry_exts().next().is_none());
+    assert!(m3u::json::Reader::new(&b"{}"[..]).is_err());
+}
+
+#[test]
+fn lines() {
+    let playlist = playlist();
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::json::LinesWriter::new(&mut bytes);
+        for entry in &playlist {
+            writer.write_entry(entry).unwrap();
+        }
+        writer.flush().unwrap();
+    }
+    assert_eq!(bytes.iter().filter(|&&b| b == b'\n').count(), playlist.len());
+
+    let mut reader = m3u::json::LinesReader::new(&bytes[..]);
+    let read: Vec<_> = reader.entry_exts().map(|e| e.unwrap()).collect();
+    assert_eq!(playlist, read);
+}
@@ -0,0 +1,71 @@
+extern crate m3u;
+
+use m3u::{NameParser, NameTemplate, TrackName};
+
+#[test]
+fn parse_names() {
+    let parser = NameParser::new();
+    let name = |artist: Option<&str>, title: &str, featuring: &[&str], version: Option<&str>| {
+        TrackName {
+            artist: artist.map(str::to_string),
+            title: title.to_string(),
+            featuring: featuring.iter().map(|s| s.to_string()).collect(),
+            version: version.map(str::to_string),
+        }
+    };
+    assert_eq!(parser.parse("Sample artist - Sample title"),
+               name(Some("Sample artist"), "Sample title", &[], None));
+    assert_eq!(parser.parse("Windowlicker"), name(None, "Windowlicker", &[], None));
+    assert_eq!(parser.parse("A feat. B & C - Song (ft. D) [Club Mix]"),
+               name(Some("A"), "Song", &["B", "C", "D"], Some("Club Mix")));
+    assert_eq!(parser.parse("Simon & Garfunkel – The Boxer (Live)"),
+               name(Some("Simon & Garfunkel"), "The Boxer", &[], Some("Live")));
+    assert_eq!(parser.parse("Artist - Title Featuring Guest"),
+               name(Some("Artist"), "Title", &["Guest"], None));
+    assert_eq!(parser.parse("Artist - Song (Mix (Remastered))"),
+               name(Some("Artist"), "Song", &[], Some("Mix (Remastered)")));
+    assert_eq!(parser.parse("Artist - Song Mix)"), name(Some("Artist"), "Song Mix)", &[], None));
+    assert_eq!(parser.parse("Artist - A Feat Of Strength"),
+               name(Some("Artist"), "A Feat Of Strength", &[], None));
+    assert_eq!(parser.parse("Artist - Song (feat Guest)"),
+               name(Some("Artist"), "Song", &["Guest"], None));
+
+    let slash = NameParser::new().separators(vec![" / "]);
+    assert_eq!(slash.parse("Artist / A - B"), name(Some("Artist"), "A - B", &[], None));
+}
+
+#[test]
+fn format_names() {
+    let parsed = NameParser::new().parse("A ft. B - Song (Radio Edit)");
+    assert_eq!(parsed.to_string(), "A - Song (feat. B) (Radio Edit)");
+    let template = NameTemplate::new("%title%[ by %artist%][ <%version%>] %unknown%");
+    assert_eq!(template.format(&parsed), "Song by A <Radio Edit> %unknown%");
+
+    let mut extinf = m3u::ExtInf::new(10.0, "Title Only");
+    let track_name = extinf.track_name(&NameParser::new());
+    extinf.set_track_name(&track_name, &NameTemplate::new("[%artist% - ]%title%"));
+    assert_eq!(extinf.name, "Title Only");
+}
+
+#[test]
+fn read_and_write_names() {
+    let playlist = "#EXTM3U\n#EXTINF:1,A / Song ft. B\na.mp3\n#EXTINF:2,Title Only\nb.mp3\n";
+    let parser = NameParser::new().separators(vec![" / "]);
+    let options = m3u::ReadOptions { name_parser: Some(parser), ..Default::default() };
+    let mut reader = m3u::Reader::new_ext_with_options(playlist.as_bytes(), options).unwrap();
+    let entry_exts: Vec<_> = reader.entry_exts().map(|e| e.unwrap()).collect();
+    let names: Vec<_> = entry_exts.iter().map(|e| &e.extinf.name[..]).collect();
+    assert_eq!(names, vec!["A - Song (feat. B)", "Title Only"]);
+
+    let template = NameTemplate::new("%title%[ by %artist%][ with %featuring%]");
+    let options = m3u::WriteOptions { name_template: Some(template), ..Default::default() };
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::Writer::new_ext_with_options(&mut bytes, options).unwrap();
+        for entry_ext in &entry_exts {
+            writer.write_entry(entry_ext).unwrap();
+        }
+    }
+    assert_eq!(String::from_utf8(bytes).unwrap(),
+               "#EXTM3U\n#EXTINF:1,Song by A with B\na.mp3\n#EXTINF:2,Title Only\nb.mp3\n");
+}
@@ -0,0 +1,67 @@
+extern crate m3u;
+
+fn expected() -> Vec<m3u::EntryExt> {
+    vec![
+        m3u::url_entry(r"http://emp.cx:8000/Listen.pls").unwrap()
+            .extend(-1.0, "Example Stream"),
+        m3u::path_entry(r"Greatest Hits\Example.ogg")
+            .extend(321.0, "Example Artist - Example title"),
+        m3u::path_entry(r"Stuff.mp3")
+            .extend(m3u::Duration::Unknown, ""),
+    ]
+}
+
+#[test]
+fn read() {
+    let mut reader = m3u::pls::Reader::open("tests/stream.pls").unwrap();
+    assert_eq!(reader.version(), Some(2));
+    let entries: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(&entries, &expected());
+
+    assert!(m3u::pls::Reader::new(&b"File1=foo.mp3\n"[..]).is_err());
+}
+
+#[test]
+fn write_read_durations() {
+    // `Known`, `Live` and `Unknown` durations all survive a round trip.
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::pls::Writer::new(&mut bytes).unwrap();
+        for entry in &expected() {
+            writer.write_entry_ext(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let pls = String::from_utf8(bytes).unwrap();
+    assert!(pls.contains("Length1=-1\n"));
+    assert!(pls.contains("Length2=321\n"));
+    assert!(!pls.contains("Length3"));
+
+    let mut reader = m3u::pls::Reader::new(pls.as_bytes()).unwrap();
+    let read: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(read, expected());
+}
+
+#[test]
+fn m3u_round_trip() {
+    // Convert the extended M3U fixture to PLS and back again.
+    let mut reader = m3u::Reader::open_ext("tests/ext.m3u").unwrap();
+    let playlist: Vec<_> = reader.entry_exts().map(|e| e.unwrap()).collect();
+
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::pls::Writer::new(&mut bytes).unwrap();
+        for entry in &playlist {
+            writer.write_entry_ext(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let pls = String::from_utf8(bytes).unwrap();
+    assert!(pls.starts_with("[playlist]\nFile1=C:\\Documents and Settings\\I\\My Music\\Sample.mp3\n\
+                             Title1=Sample artist - Sample title\nLength1=123\n"));
+    assert!(pls.ends_with("NumberOfEntries=4\nVersion=2\n"));
+
+    let mut reader = m3u::pls::Reader::new(pls.as_bytes()).unwrap();
+    let read: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(playlist, read);
+}
@@ -0,0 +1,55 @@
+extern crate m3u;
+
+use m3u::{DriveMapping, PathPrefix, PortablePath};
+
+#[test]
+fn parse() {
+    let path = PortablePath::parse(r"c:\Documents and Settings\I\My Music\Sample.mp3");
+    assert_eq!(path.prefix, Some(PathPrefix::Drive('C')));
+    assert!(path.is_absolute());
+    assert_eq!(path.file_name(), Some("Sample.mp3"));
+    assert_eq!(path.parent().unwrap().to_string(), r"C:\Documents and Settings\I\My Music");
+
+    let unc = PortablePath::parse(r"\\server\media\Music\Foo.mp3");
+    let share = PathPrefix::Unc { server: "server".to_string(), share: "media".to_string() };
+    assert_eq!(unc.prefix, Some(share));
+    assert_eq!(unc.components, vec!["Music", "Foo.mp3"]);
+
+    let relative = PortablePath::parse(r"..\Other Music/./Bar.mp3");
+    assert!(!relative.is_absolute());
+    assert_eq!(relative.components, vec!["..", "Other Music", "Bar.mp3"]);
+    assert_eq!(PortablePath::parse(r"D:\More Music").join(&relative).to_string(),
+               r"D:\More Music\..\Other Music\Bar.mp3");
+    assert_eq!(path.join(&PortablePath::parse(r"\Root.mp3")).to_string(), r"C:\Root.mp3");
+
+    let entry = m3u::path_entry(r"Greatest Hits\Example.ogg");
+    assert_eq!(entry.portable_path().unwrap().file_name(), Some("Example.ogg"));
+}
+
+#[test]
+fn to_native() {
+    let mapping = DriveMapping::new()
+        .drive('c', "/mnt/c")
+        .share("SERVER", "Media", "/mnt/media");
+    let native = |path: &str| PortablePath::parse(path).to_native(&mapping);
+    let expected = |path: &str| Some(std::path::PathBuf::from(path));
+
+    assert_eq!(native(r"Greatest Hits\Example.ogg"), expected("Greatest Hits/Example.ogg"));
+    if cfg!(unix) {
+        assert_eq!(native(r"C:\Music\Foo.mp3"), expected("/mnt/c/Music/Foo.mp3"));
+        assert_eq!(native(r"\\server\media\Foo.mp3"), expected("/mnt/media/Foo.mp3"));
+        assert_eq!(native(r"\Music\Foo.mp3"), expected("/Music/Foo.mp3"));
+        assert_eq!(native(r"D:\Music\Foo.mp3"), None);
+    }
+
+    // Remapping a drive or share replaces its previous mount point.
+    let remapped = mapping.clone()
+        .drive('C', "/media/c")
+        .share("server", "media", "/media/share");
+    let expected = DriveMapping::new()
+        .drive('c', "/media/c")
+        .share("server", "media", "/media/share");
+    assert_eq!(remapped, expected);
+    let share = PathPrefix::Unc { server: "Server".to_string(), share: "MEDIA".to_string() };
+    assert_eq!(remapped.mount_point(&share), Some(std::path::Path::new("/media/share")));
+}
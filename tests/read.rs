@@ -42,3 +42,118 @@ fn ext() {
 
     assert_eq!(&entries, &expected);
 }
+
+#[test]
+fn ext_attributes() {
+    let playlist = "#EXTM3U\n\
+                    #EXTINF:-1 tvg-id=\"news.1\" group-title=\"News, Weather\",News Channel\n\
+                    http://www.example.com/news.m3u8\n";
+    let mut reader = m3u::Reader::new_ext(playlist.as_bytes()).unwrap();
+    let entries: Vec<_> = reader.entry_exts().map(|e| e.unwrap()).collect();
+
+    let mut expected = m3u::url_entry("http://www.example.com/news.m3u8").unwrap()
+        .extend(-1.0, "News Channel");
+    expected.extinf.set_attribute("tvg-id", "news.1");
+    expected.extinf.set_attribute("group-title", "News, Weather");
+    assert_eq!(&entries, &[expected]);
+    assert_eq!(entries[0].extinf.attribute("group-title"), Some("News, Weather"));
+
+    // A leading byte order mark is skipped.
+    let playlist = b"\xef\xbb\xbf#EXTM3U\n#EXTINF:1,A\na.mp3\n";
+    let mut reader = m3u::Reader::new_ext(&playlist[..]).unwrap();
+    let expected = m3u::path_entry("a.mp3").extend(1.0, "A");
+    assert_eq!(reader.entry_exts().next().unwrap().unwrap(), expected);
+}
+
+#[test]
+fn resolve() {
+    let url = m3u::Url::parse("http://host/lists/a.m3u").unwrap();
+    let url_base = m3u::Base::from_playlist_url(url);
+    let dir_base = m3u::Base::from_playlist_path("/music/a.m3u");
+
+    let entry = m3u::path_entry(r"Greatest Hits\Example.ogg");
+    assert_eq!(entry.resolve(&url_base),
+               m3u::url_entry("http://host/lists/Greatest%20Hits/Example.ogg").unwrap());
+    if cfg!(unix) {
+        assert_eq!(entry.resolve(&dir_base), m3u::path_entry("/music/Greatest Hits/Example.ogg"));
+    }
+    assert_eq!(m3u::path_entry("/root.mp3").resolve(&url_base),
+               m3u::url_entry("http://host/root.mp3").unwrap());
+    assert_eq!(m3u::path_entry("../Track #1? 100%.mp3").resolve(&url_base),
+               m3u::url_entry("http://host/Track%20%231%3F%20100%25.mp3").unwrap());
+
+    // Absolute paths and URLs are left untouched.
+    let unchanged = [
+        m3u::path_entry(r"C:\Music\Foo.mp3"),
+        m3u::path_entry(r"\\server\share\a.mp3"),
+        m3u::path_entry("//server/share/a.mp3"),
+        m3u::path_entry(r"/\server\share\a.mp3"),
+        m3u::url_entry("http://www.example.com/~user/Mine.mp3").unwrap(),
+    ];
+    for entry in unchanged.iter() {
+        assert_eq!(&entry.resolve(&url_base), entry);
+        assert_eq!(&entry.resolve(&dir_base), entry);
+    }
+
+    // Entries may be resolved as they are read.
+    let options = m3u::ReadOptions { base: Some(url_base), ..Default::default() };
+    let mut resolving = m3u::Reader::new_with_options(&b"Stuff.mp3\n"[..], options);
+    assert_eq!(resolving.entries().next().unwrap().unwrap(),
+               m3u::url_entry("http://host/lists/Stuff.mp3").unwrap());
+}
+
+#[test]
+fn uris() {
+    let playlist = "file:///music/My%20Album/Track%201.mp3\n\
+                    file:///C:/My%20Music/Sample.mp3\n\
+                    file://nas/media/Foo.mp3\n\
+                    spotify:track:6rqhFgbbKwnb9MLmUQDhG6\n\
+                    C:\\Music\\Bar.mp3\n";
+    let mut reader = m3u::Reader::new(playlist.as_bytes());
+    let entries: Vec<_> = reader.entries().map(|e| e.unwrap()).collect();
+    assert_eq!(entries, vec![
+        m3u::path_entry("/music/My Album/Track 1.mp3"),
+        m3u::path_entry(r"C:\My Music\Sample.mp3"),
+        m3u::path_entry(r"\\nas\media\Foo.mp3"),
+        m3u::Entry::Uri(m3u::Url::parse("spotify:track:6rqhFgbbKwnb9MLmUQDhG6").unwrap()),
+        m3u::path_entry(r"C:\Music\Bar.mp3"),
+    ]);
+    assert!(entries[3].is_uri());
+
+    // Relative paths containing a colon are not URIs.
+    let paths = ["Artist: Title.mp3", "Beatles: Help.mp3", "Artist:Title.mp3", "artist:title.mp3",
+                 "track01:intro.mp3"];
+    for path in &paths {
+        let mut reader = m3u::Reader::new(path.as_bytes());
+        assert_eq!(reader.entries().next().unwrap().unwrap(), m3u::path_entry(path));
+
+        let mut bytes = Vec::new();
+        m3u::Writer::new(&mut bytes).write_entry(&m3u::path_entry(path)).unwrap();
+        let mut reader = m3u::Reader::new(&bytes[..]);
+        assert_eq!(reader.entries().next().unwrap().unwrap(), m3u::path_entry(path));
+    }
+
+    // `file:` URLs may optionally be kept as URLs.
+    let options = m3u::ReadOptions { keep_file_urls: true, ..Default::default() };
+    let mut reader = m3u::Reader::new_with_options(playlist.as_bytes(), options);
+    assert!(reader.entries().next().unwrap().unwrap().is_url());
+}
+
+#[test]
+fn durations() {
+    let playlist = "#EXTM3U\n#EXTINF:123,A\na.mp3\n#EXTINF:1.25,B\nb.mp3\n#EXTINF:-1,C\nc.mp3\n";
+    let mut reader = m3u::Reader::new_ext(playlist.as_bytes()).unwrap();
+    let durations: Vec<_> = reader.entry_exts().map(|e| e.unwrap().extinf.duration).collect();
+    assert_eq!(durations, vec![
+        m3u::Duration::from_secs(123),
+        m3u::Duration::Known(std::time::Duration::from_millis(1_250)),
+        m3u::Duration::Live,
+    ]);
+    assert_eq!(m3u::Duration::from(f64::NAN), m3u::Duration::Unknown);
+    assert_eq!(durations[1].as_secs_f64(), Some(1.25));
+
+    // `EntryExt`s may be used as keys now that durations are `Eq` and `Hash`.
+    let mut set = std::collections::HashSet::new();
+    assert!(set.insert(m3u::path_entry("a.mp3").extend(123.0, "A")));
+    assert!(!set.insert(m3u::path_entry("a.mp3").extend(m3u::Duration::from_secs(123), "A")));
+}
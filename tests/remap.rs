@@ -0,0 +1,64 @@
+extern crate m3u;
+
+use m3u::Remapper;
+
+#[test]
+fn remap_entries() {
+    let remapper = Remapper::new()
+        .rule(r"D:\More Music", "/mnt/music")
+        .rule("/home/user/Music", r"\\nas\music");
+    let mut entries = vec![
+        m3u::path_entry(r"d:/MORE MUSIC\Foo.mp3"),
+        m3u::path_entry("/home/user/Music/Albums/Bar.mp3"),
+        m3u::path_entry(r"D:\More Musical\Baz.mp3"),
+        m3u::url_entry("http://www.example.com/~user/Mine.mp3").unwrap(),
+    ];
+    let report = remapper.remap(&mut entries);
+    assert_eq!(report.remapped, 2);
+    assert_eq!(report.unmatched, vec![2]);
+    assert_eq!(entries[0], m3u::path_entry("/mnt/music/Foo.mp3"));
+    assert_eq!(entries[1], m3u::path_entry(r"\\nas\music\Albums\Bar.mp3"));
+
+    let case_sensitive = remapper.case_sensitive(true);
+    assert_eq!(case_sensitive.remap_entry(&m3u::path_entry(r"D:\more music\Foo.mp3")), None);
+}
+
+#[test]
+fn remap_files() {
+    let dir = std::env::temp_dir().join(format!("m3u-remap-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let m3u_path = dir.join("mixed.m3u");
+    let pls_path = dir.join("stream.pls");
+    std::fs::copy("tests/mixed.m3u", &m3u_path).unwrap();
+    std::fs::copy("tests/stream.pls", &pls_path).unwrap();
+
+    let remapper = Remapper::new()
+        .rule(r"D:\More Music", "/mnt/music")
+        .rule("Greatest Hits", "/mnt/music/Greatest Hits");
+    let results = remapper.remap_files(&[&m3u_path, &pls_path, &dir.join("missing.m3u")]);
+    let reports: Vec<_> = results.iter().map(|(_, r)| r.as_ref().ok()).collect();
+    assert_eq!(reports[0].unwrap().remapped, 1);
+    assert_eq!(reports[0].unwrap().unmatched, vec![0, 1, 2, 4]);
+    assert_eq!(reports[1].unwrap().remapped, 1);
+    assert!(reports[2].is_none());
+
+    // M3U files are rewritten line by line, leaving everything else untouched.
+    let original = std::fs::read_to_string("tests/mixed.m3u").unwrap();
+    let remapped = std::fs::read_to_string(&m3u_path).unwrap();
+    assert_eq!(remapped, original.replace(r"D:\More Music\Foo.mp3", "/mnt/music/Foo.mp3"));
+    let mut reader = m3u::pls::Reader::open(&pls_path).unwrap();
+    let expected = m3u::path_entry("/mnt/music/Greatest Hits/Example.ogg");
+    assert_eq!(reader.entries().nth(1), Some(expected));
+
+    // Lines of legacy Latin-1 playlists that are not valid UTF-8 are kept byte for byte.
+    let latin1_path = dir.join("latin1.m3u");
+    let latin1 = b"D:\\More Music\\Caf\xe9.mp3\r\nD:\\More Music\\Foo.mp3\r\n";
+    std::fs::write(&latin1_path, &latin1[..]).unwrap();
+    let report = remapper.remap_file(&latin1_path).unwrap();
+    assert_eq!(report.remapped, 1);
+    assert_eq!(report.unmatched, vec![0]);
+    assert_eq!(std::fs::read(&latin1_path).unwrap(),
+               &b"D:\\More Music\\Caf\xe9.mp3\r\n/mnt/music/Foo.mp3\r\n"[..]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "serde")]
+
+extern crate m3u;
+extern crate serde_json;
+
+#[test]
+fn representation() {
+    let mut entry_ext = m3u::path_entry(r"Greatest Hits\Example.ogg")
+        .extend(321.0, "Example Artist - Example title");
+    let json = serde_json::to_value(&entry_ext).unwrap();
+    assert_eq!(json, serde_json::json!({
+        "entry": { "path": r"Greatest Hits\Example.ogg" },
+        "extinf": { "duration_secs": 321.0, "name": "Example Artist - Example title" },
+    }));
+
+    entry_ext.entry = m3u::url_entry("http://emp.cx:8000/Listen.pls").unwrap();
+    entry_ext.extinf.set_attribute("tvg-id", "b");
+    entry_ext.extinf.set_attribute("group-title", "a");
+    let json = serde_json::to_string(&entry_ext).unwrap();
+    assert_eq!(json, "{\"entry\":{\"url\":\"http://emp.cx:8000/Listen.pls\"},\
+                      \"extinf\":{\"duration_secs\":321.0,\"name\":\"Example Artist - Example title\",\
+                      \"attributes\":{\"tvg-id\":\"b\",\"group-title\":\"a\"}}}");
+
+    let durations = [m3u::Duration::from_secs(1), m3u::Duration::Live, m3u::Duration::Unknown];
+    let extinfs: Vec<_> = durations.iter().map(|&d| m3u::ExtInf::new(d, "")).collect();
+    assert_eq!(serde_json::to_value(&extinfs).unwrap(), serde_json::json!([
+        { "duration_secs": 1.0, "name": "" },
+        { "duration_secs": -1.0, "name": "" },
+        { "duration_secs": null, "name": "" },
+    ]));
+}
+
+#[test]
+fn round_trip() {
+    let playlist = vec![
+        m3u::path_entry(r"C:\Documents and Settings\I\My Music\Sample.mp3")
+            .extend(123.0, "Sample artist - Sample title"),
+        m3u::url_entry(r"http://www.example.com/~user/Mine.mp3").unwrap()
+            .extend(-1.0, "Mine"),
+        m3u::path_entry("Stuff.mp3").extend(m3u::Duration::Unknown, ""),
+    ];
+    let json = serde_json::to_string(&playlist).unwrap();
+    let read: Vec<m3u::EntryExt> = serde_json::from_str(&json).unwrap();
+    assert_eq!(playlist, read);
+
+    assert!(serde_json::from_str::<m3u::Entry>(r#"{"url": "not a url"}"#).is_err());
+}
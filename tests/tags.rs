@@ -0,0 +1,89 @@
+#![cfg(feature = "tags")]
+
+extern crate m3u;
+
+use std::path::Path;
+
+/// Write one second of silent 8kHz mono 16-bit PCM with the given RIFF INFO tags.
+fn write_wav(path: &Path, info: &[(&[u8; 4], &str)]) {
+    let chunk = |id: &[u8], data: &[u8]| {
+        let mut chunk = id.to_vec();
+        chunk.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        chunk.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            chunk.push(0);
+        }
+        chunk
+    };
+    let mut fmt = Vec::new();
+    for &(value, size) in &[(1u32, 2), (1, 2), (8000, 4), (16000, 4), (2, 2), (16, 2)] {
+        fmt.extend_from_slice(&value.to_le_bytes()[..size]);
+    }
+    let mut list = b"INFO".to_vec();
+    for &(id, value) in info {
+        let mut value = value.as_bytes().to_vec();
+        value.push(0);
+        list.extend(chunk(id, &value));
+    }
+    let mut body = b"WAVE".to_vec();
+    body.extend(chunk(b"fmt ", &fmt));
+    body.extend(chunk(b"LIST", &list));
+    body.extend(chunk(b"data", &vec![0; 16000]));
+    std::fs::write(path, chunk(b"RIFF", &body)).unwrap();
+}
+
+#[test]
+fn extinf_from_tags() {
+    let dir = std::env::temp_dir().join(format!("m3u-tags-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    write_wav(&dir.join("tagged.wav"), &[(b"IART", "Aphex Twin"), (b"INAM", "Windowlicker")]);
+    write_wav(&dir.join("untagged.wav"), &[]);
+
+    let extinf = m3u::tags::extinf(dir.join("tagged.wav")).unwrap();
+    assert_eq!(extinf.name, "Aphex Twin - Windowlicker");
+    assert_eq!(extinf.duration, m3u::Duration::from_secs(1));
+
+    let entries = vec![
+        m3u::path_entry("untagged.wav"),
+        m3u::path_entry("missing.wav"),
+        m3u::url_entry("http://example.com/stream.mp3").unwrap(),
+    ];
+    let upgraded = m3u::tags::upgrade(entries.clone(), &m3u::Base::Dir(dir.clone()));
+    assert_eq!(upgraded, vec![
+        entries[0].clone().extend(1.0, "untagged"),
+        entries[1].clone().extend(m3u::Duration::Unknown, "missing"),
+        entries[2].clone().extend(m3u::Duration::Unknown, "stream"),
+    ]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn verify_and_fix_durations() {
+    let dir = std::env::temp_dir().join(format!("m3u-durations-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    write_wav(&dir.join("one.wav"), &[]);
+
+    let mut entry_exts = vec![
+        m3u::path_entry("one.wav").extend(1.4, "Close enough"),
+        m3u::path_entry("one.wav").extend(3.0, "Drifted"),
+        m3u::path_entry("one.wav").extend(-1.0, "Unknown"),
+        m3u::path_entry("missing.wav").extend(1.0, "Missing"),
+        m3u::url_entry("http://example.com/stream.mp3").unwrap().extend(-1.0, "Stream"),
+    ];
+    let base = m3u::Base::Dir(dir.clone());
+    let report = m3u::tags::verify_durations(&entry_exts, &base, 0.5);
+    assert_eq!(report.checked, 3);
+    assert_eq!(report.unreadable, vec![3]);
+    let mismatched: Vec<_> = report.mismatches.iter().map(|m| m.index).collect();
+    assert_eq!(mismatched, vec![1, 2]);
+    assert_eq!(report.mismatches[0].listed, m3u::Duration::from_secs(3));
+    assert_eq!(report.mismatches[0].actual, std::time::Duration::from_secs(1));
+
+    let fixed = m3u::tags::fix_durations(&mut entry_exts, &base, 0.5);
+    assert_eq!(fixed, report);
+    let durations: Vec<_> = entry_exts.iter().map(|e| e.extinf.duration.to_string()).collect();
+    assert_eq!(durations, vec!["1.4", "1", "1", "1", "-1"]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,25 @@
+extern crate m3u;
+
+use m3u::PathProblemKind;
+
+#[test]
+fn validate_paths() {
+    // Paths are resolved against the directory of the playlist, which need not exist.
+    let entries = vec![
+        m3u::path_entry("ext.m3u"),
+        m3u::path_entry("missing.mp3"),
+        m3u::url_entry("http://www.example.com/~user/Mine.mp3").unwrap(),
+        m3u::path_entry("."),
+    ];
+    let report = m3u::validate_paths("tests/playlist.m3u", &entries);
+    assert_eq!(report.checked, 3);
+    assert!(!report.is_ok());
+
+    let kinds: Vec<_> = report.problems.iter().map(|p| (p.index, p.kind)).collect();
+    assert_eq!(kinds, vec![(1, PathProblemKind::Missing), (3, PathProblemKind::Directory)]);
+    assert_eq!(report.problems[0].path, std::path::Path::new("tests/missing.mp3"));
+    assert_eq!(report.problems_of_kind(PathProblemKind::Missing).count(), 1);
+
+    #[cfg(feature = "parallel")]
+    assert_eq!(m3u::validate_paths_parallel("tests/playlist.m3u", &entries), report);
+}
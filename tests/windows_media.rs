@@ -0,0 +1,76 @@
+#![cfg(feature = "xml")]
+
+extern crate m3u;
+
+/// Write the given playlist as extended M3U and read it back again.
+fn m3u_round_trip(playlist: &[m3u::EntryExt]) -> Vec<m3u::EntryExt> {
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::Writer::new_ext(&mut bytes).unwrap();
+        for entry in playlist {
+            writer.write_entry(entry).unwrap();
+        }
+    }
+    let mut reader = m3u::Reader::new_ext(&bytes[..]).unwrap();
+    reader.entry_exts().map(|e| e.unwrap()).collect()
+}
+
+#[test]
+fn asx() {
+    let mut mine = m3u::url_entry("http://www.example.com/~user/Mine.mp3").unwrap()
+        .extend(321.0, "Example title");
+    mine.extinf.set_attribute("creator", "Example Artist");
+    let expected = vec![
+        mine,
+        m3u::path_entry(r"D:\More Music\Foo.mp3").extend(m3u::Duration::Unknown, ""),
+        m3u::url_entry("http://emp.cx:8000/Listen.asx").unwrap().extend(m3u::Duration::Unknown, ""),
+    ];
+
+    let mut reader = m3u::asx::Reader::open("tests/example.asx").unwrap();
+    assert_eq!(reader.title(), Some("Example Playlist"));
+    let entries: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(entries, expected);
+
+    // ASX -> M3U -> ASX.
+    let entries = m3u_round_trip(&entries);
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::asx::Writer::with_title(&mut bytes, Some("Example Playlist")).unwrap();
+        for entry in &entries {
+            writer.write_entry_ext(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let mut reader = m3u::asx::Reader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.title(), Some("Example Playlist"));
+    assert_eq!(reader.entry_exts().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn wpl() {
+    let expected = vec![
+        m3u::path_entry(r"..\Other Music\Bar.mp3").extend(m3u::Duration::Unknown, ""),
+        m3u::path_entry(r"Greatest Hits\Example.ogg")
+            .extend(321.0, "Example Artist - Example title"),
+    ];
+
+    let mut reader = m3u::wpl::Reader::open("tests/example.wpl").unwrap();
+    assert_eq!(reader.title(), Some("Example Playlist"));
+    let entries: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(entries, expected);
+
+    // WPL -> M3U -> WPL.
+    let entries = m3u_round_trip(&entries);
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::wpl::Writer::new(&mut bytes).unwrap();
+        for entry in &entries {
+            writer.write_entry_ext(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let mut reader = m3u::wpl::Reader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.title(), None);
+    assert_eq!(reader.entries().collect::<Vec<_>>(),
+               expected.into_iter().map(|e| e.entry).collect::<Vec<_>>());
+}
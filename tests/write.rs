@@ -14,7 +14,7 @@ fn entry() {
         m3u::url_entry(r"http://www.example.com/~user/Mine.mp3").unwrap(),
     ];
 
-    const FILEPATH: &'static str = "tests/playlist.m3u";
+    const FILEPATH: &str = "tests/playlist.m3u";
 
     if std::path::Path::new(FILEPATH).exists() {
         std::fs::remove_file(FILEPATH).unwrap();
@@ -55,7 +55,7 @@ fn entry_ext() {
             .extend(321.0, "Example Artist - Example title"),
     ];
 
-    const FILEPATH: &'static str = "tests/playlist_ext.m3u";
+    const FILEPATH: &str = "tests/playlist_ext.m3u";
 
     if std::path::Path::new(FILEPATH).exists() {
         std::fs::remove_file(FILEPATH).unwrap();
@@ -80,3 +80,208 @@ fn entry_ext() {
 
     std::fs::remove_file(FILEPATH).unwrap();
 }
+
+#[test]
+fn path_options() {
+    let playlist = vec![
+        m3u::path_entry("/music/Alternative/Band - Song.mp3"),
+        m3u::path_entry("/other/Stuff.mp3"),
+        m3u::path_entry(r"Greatest Hits\Example.ogg"),
+        m3u::path_entry(r"D:\More Music\Foo.mp3"),
+        m3u::url_entry("http://www.example.com/~user/Mine.mp3").unwrap(),
+    ];
+    let write = |options: m3u::WriteOptions| {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = m3u::Writer::new_with_options(&mut bytes, options);
+            for entry in &playlist {
+                writer.write_entry(entry).unwrap();
+            }
+        }
+        String::from_utf8(bytes).unwrap()
+    };
+
+    let relative = write(m3u::WriteOptions {
+        paths: m3u::PathMode::Relative("/music/lists".into()),
+        ..Default::default()
+    });
+    assert_eq!(relative, "../Alternative/Band - Song.mp3\n\
+                          ../../other/Stuff.mp3\n\
+                          Greatest Hits\\Example.ogg\n\
+                          D:\\More Music\\Foo.mp3\n\
+                          http://www.example.com/~user/Mine.mp3\n");
+
+    let absolute = write(m3u::WriteOptions {
+        paths: m3u::PathMode::Absolute("/music/lists/../".into()),
+        separator: Some(m3u::Separator::Backslash),
+        ..Default::default()
+    });
+    assert_eq!(absolute, "\\music\\Alternative\\Band - Song.mp3\n\
+                          \\other\\Stuff.mp3\n\
+                          \\music\\Greatest Hits\\Example.ogg\n\
+                          D:\\More Music\\Foo.mp3\n\
+                          http://www.example.com/~user/Mine.mp3\n");
+
+    let slashes = write(m3u::WriteOptions {
+        paths: m3u::PathMode::Verbatim,
+        separator: Some(m3u::Separator::Slash),
+        ..Default::default()
+    });
+    assert!(slashes.contains("Greatest Hits/Example.ogg\nD:/More Music/Foo.mp3\n"));
+
+    let file_urls = write(m3u::WriteOptions { file_urls: true, ..Default::default() });
+    assert_eq!(file_urls, "file:///music/Alternative/Band%20-%20Song.mp3\n\
+                           file:///other/Stuff.mp3\n\
+                           Greatest Hits\\Example.ogg\n\
+                           file:///D:/More%20Music/Foo.mp3\n\
+                           http://www.example.com/~user/Mine.mp3\n");
+}
+
+#[test]
+fn duration_formats() {
+    let durations = [
+        m3u::Duration::from(0.1 + 0.2),
+        m3u::Duration::from(123.5),
+        m3u::Duration::from(9.0091),
+        m3u::Duration::Live,
+    ];
+    let write = |format: m3u::DurationFormat| {
+        let options = m3u::WriteOptions { durations: format, ..Default::default() };
+        let mut bytes = Vec::new();
+        {
+            let mut writer = m3u::Writer::new_ext_with_options(&mut bytes, options).unwrap();
+            for &duration in &durations {
+                writer.write_entry(&m3u::path_entry("a.mp3").extend(duration, "A")).unwrap();
+            }
+            writer.flush().unwrap();
+        }
+        let text = String::from_utf8(bytes.clone()).unwrap();
+        let written: Vec<_> = text.lines()
+            .filter_map(|line| line.strip_prefix("#EXTINF:"))
+            .map(|line| line.trim_end_matches(",A").to_string())
+            .collect();
+        let mut reader = m3u::Reader::new_ext(&bytes[..]).unwrap();
+        let read: Vec<_> = reader.entry_exts().map(|e| e.unwrap().extinf.duration).collect();
+        (written, read)
+    };
+
+    let (written, read) = write(m3u::DurationFormat::Shortest);
+    assert_eq!(written, vec!["0.3", "123.5", "9.0091", "-1"]);
+    assert_eq!(read, durations);
+
+    let (written, _) = write(m3u::DurationFormat::Integer);
+    assert_eq!(written, vec!["0", "124", "9", "-1"]);
+
+    // Reading back yields the same value within the chosen precision.
+    let (written, read) = write(m3u::DurationFormat::Decimals(2));
+    assert_eq!(written, vec!["0.30", "123.50", "9.01", "-1"]);
+    for (read, original) in read.iter().zip(&durations).take(3) {
+        let difference = read.as_secs_f64().unwrap() - original.as_secs_f64().unwrap();
+        assert!(difference.abs() <= 0.005);
+    }
+
+    let (written, _) = write(m3u::DurationFormat::Hls);
+    assert_eq!(written, vec!["0.300", "123.500", "9.009", "0.000"]);
+}
+
+#[test]
+fn escape_policies() {
+    let mut entry_ext = m3u::path_entry("#1 Hit\n.mp3").extend(1.0, "Line\nBreak, 100%");
+    entry_ext.extinf.set_attribute("tvg name", "Say \"Hi\"");
+    let write = |escape: m3u::EscapePolicy| {
+        let options = m3u::WriteOptions { escape, ..Default::default() };
+        let mut bytes = Vec::new();
+        let result = m3u::Writer::new_ext_with_options(&mut bytes, options).unwrap()
+            .write_entry(&entry_ext);
+        result.map(|()| bytes)
+    };
+
+    let err = write(m3u::EscapePolicy::Reject).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = err.get_ref().unwrap().downcast_ref::<m3u::UnsafeValueError>().unwrap();
+    assert_eq!(err.field, m3u::UnsafeField::AttributeKey);
+
+    // Escaped values only round-trip when read with `unescape`.
+    let bytes = write(m3u::EscapePolicy::Escape).unwrap();
+    assert_eq!(String::from_utf8(bytes.clone()).unwrap(),
+               "#EXTM3U\n#EXTINF:1 tvg%20name=\"Say %22Hi%22\",Line%0ABreak, 100%25\n\
+                %231 Hit%0A.mp3\n");
+    let options = m3u::ReadOptions { unescape: true, ..Default::default() };
+    let mut reader = m3u::Reader::new_ext_with_options(&bytes[..], options).unwrap();
+    let read: Vec<_> = reader.entry_exts().map(|entry| entry.unwrap()).collect();
+    assert_eq!(read, vec![entry_ext.clone()]);
+
+    let bytes = write(m3u::EscapePolicy::Sanitize).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(),
+               "#EXTM3U\n#EXTINF:1 tvg_name=\"Say 'Hi'\",Line Break, 100%\n./#1 Hit .mp3\n");
+
+    // Padding and paths that would be read as URLs or URIs round-trip too.
+    let entry_exts = vec![
+        m3u::path_entry(" Padded.mp3 ").extend(1.0, " Padded "),
+        m3u::path_entry("http://x/a.mp3").extend(1.0, "Url"),
+        m3u::path_entry("spotify:track:1").extend(1.0, "Uri"),
+        m3u::path_entry("Beatles: Help.mp3").extend(1.0, "Colon"),
+    ];
+    let write_all = |escape: m3u::EscapePolicy| {
+        let options = m3u::WriteOptions { escape, ..Default::default() };
+        let mut bytes = Vec::new();
+        {
+            let mut writer = m3u::Writer::new_ext_with_options(&mut bytes, options).unwrap();
+            for entry_ext in &entry_exts {
+                writer.write_entry(entry_ext).unwrap();
+            }
+        }
+        String::from_utf8(bytes).unwrap()
+    };
+    let escaped = write_all(m3u::EscapePolicy::Escape);
+    assert_eq!(escaped,
+               "#EXTM3U\n#EXTINF:1,%20Padded%20\n%20Padded.mp3%20\n\
+                #EXTINF:1,Url\nhttp%3A//x/a.mp3\n#EXTINF:1,Uri\nspotify%3Atrack%3A1\n\
+                #EXTINF:1,Colon\nBeatles: Help.mp3\n");
+    let options = m3u::ReadOptions { unescape: true, ..Default::default() };
+    let mut reader = m3u::Reader::new_ext_with_options(escaped.as_bytes(), options).unwrap();
+    let read: Vec<_> = reader.entry_exts().map(|entry| entry.unwrap()).collect();
+    assert_eq!(read, entry_exts);
+
+    assert_eq!(write_all(m3u::EscapePolicy::Sanitize),
+               "#EXTM3U\n#EXTINF:1,Padded\nPadded.mp3\n#EXTINF:1,Url\n./http://x/a.mp3\n\
+                #EXTINF:1,Uri\n./spotify:track:1\n#EXTINF:1,Colon\nBeatles: Help.mp3\n");
+
+    // Paths written as `file://` URLs are already encoded.
+    let escape = m3u::EscapePolicy::Escape;
+    let options = m3u::WriteOptions { escape, file_urls: true, ..Default::default() };
+    let mut bytes = Vec::new();
+    m3u::Writer::new_with_options(&mut bytes, options)
+        .write_entry(&m3u::path_entry("/music/100% Hits.mp3"))
+        .unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), "file:///music/100%25%20Hits.mp3\n");
+}
+
+#[test]
+fn line_endings() {
+    let input = "#EXTM3U\r\n#EXTINF:1,A\r\na.mp3\r\n#EXTINF:2,B\r\nb.mp3\r\n";
+    let mut reader = m3u::Reader::new_ext(input.as_bytes()).unwrap();
+    assert_eq!(reader.line_ending(), Some(m3u::LineEnding::Crlf));
+    let entry_exts: Vec<_> = reader.entry_exts().map(|entry| entry.unwrap()).collect();
+
+    let write = |options: m3u::WriteOptions| {
+        let mut bytes = Vec::new();
+        {
+            let mut writer = m3u::Writer::new_ext_with_options(&mut bytes, options).unwrap();
+            for entry_ext in &entry_exts {
+                writer.write_entry(entry_ext).unwrap();
+            }
+            writer.flush().unwrap();
+        }
+        String::from_utf8(bytes).unwrap()
+    };
+
+    // Matching the input's line endings reproduces it exactly.
+    let line_ending = reader.line_ending().unwrap_or_default();
+    assert_eq!(write(m3u::WriteOptions { line_ending, ..Default::default() }), input);
+
+    let lf = write(m3u::WriteOptions { omit_final_line_ending: true, ..Default::default() });
+    assert_eq!(lf, "#EXTM3U\n#EXTINF:1,A\na.mp3\n#EXTINF:2,B\nb.mp3");
+    assert_eq!(m3u::LineEnding::detect(&lf), Some(m3u::LineEnding::Lf));
+    assert_eq!(m3u::LineEnding::detect("a.mp3"), None);
+}
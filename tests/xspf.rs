@@ -0,0 +1,97 @@
+#![cfg(feature = "xml")]
+
+extern crate m3u;
+
+fn expected() -> Vec<m3u::EntryExt> {
+    let mut mine = m3u::url_entry("http://www.example.com/~user/Mine.mp3").unwrap()
+        .extend(321.0, "Example title");
+    mine.extinf.set_attribute("creator", "Example Artist");
+    mine.extinf.set_attribute("album", "Greatest Hits");
+    mine.extinf.set_attribute("image", "http://www.example.com/cover.jpg");
+    vec![
+        mine,
+        m3u::url_entry("http://emp.cx:8000/Listen.pls").unwrap()
+            .extend(m3u::Duration::Unknown, "Example Stream"),
+    ]
+}
+
+#[test]
+fn read() {
+    let mut reader = m3u::xspf::Reader::open("tests/example.xspf").unwrap();
+    assert_eq!(reader.metadata().title, Some("Example Playlist".to_string()));
+    assert_eq!(reader.metadata().extensions[0].text(), "Example annotation & notes");
+    let entries: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(entries, expected());
+}
+
+#[test]
+fn round_trip_tracks() {
+    // Unknown elements such as the VLC `<extension>` must survive a round trip.
+    let mut reader = m3u::xspf::Reader::open("tests/example.xspf").unwrap();
+    let metadata = reader.metadata().clone();
+    let tracks: Vec<_> = reader.tracks().collect();
+    assert_eq!(tracks[0].extensions.len(), 1);
+    assert_eq!(tracks[0].extensions[0].local_name(), "extension");
+
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::xspf::Writer::with_metadata(&mut bytes, &metadata).unwrap();
+        for track in &tracks {
+            writer.write_track(track).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    let mut reader = m3u::xspf::Reader::new(&bytes[..]).unwrap();
+    assert_eq!(reader.metadata(), &metadata);
+    let read: Vec<_> = reader.tracks().collect();
+    assert_eq!(read, tracks);
+    let vlc_id = read[0].extensions[0].element("id").unwrap();
+    assert_eq!(vlc_id.name, "vlc:id");
+    assert_eq!(vlc_id.text(), "0");
+}
+
+#[test]
+fn round_trip_entry_exts() {
+    let playlist = expected();
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::xspf::Writer::new(&mut bytes).unwrap();
+        for entry in &playlist {
+            writer.write_entry_ext(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let mut reader = m3u::xspf::Reader::new(&bytes[..]).unwrap();
+    let read: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(read, playlist);
+
+    assert!(m3u::xspf::Reader::new(&b"<asx version=\"3.0\"></asx>"[..]).is_err());
+}
+
+#[test]
+fn path_locations() {
+    let playlist = vec![
+        m3u::path_entry("/music/Greatest Hits/#1.mp3").extend(1.0, "Absolute"),
+        m3u::path_entry("Greatest Hits/100% Example.ogg").extend(2.0, "Relative"),
+        m3u::path_entry("Beatles: Help.mp3").extend(3.0, "Colon"),
+    ];
+    let mut bytes = Vec::new();
+    {
+        let mut writer = m3u::xspf::Writer::new(&mut bytes).unwrap();
+        for entry in &playlist {
+            writer.write_entry_ext(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+    let text = String::from_utf8(bytes.clone()).unwrap();
+    assert!(text.contains("<location>file:///music/Greatest%20Hits/%231.mp3</location>"));
+    assert!(text.contains("<location>Greatest%20Hits/100%25%20Example.ogg</location>"));
+    assert!(text.contains("<location>./Beatles:%20Help.mp3</location>"));
+
+    let mut reader = m3u::xspf::Reader::new(&bytes[..]).unwrap();
+    let read: Vec<_> = reader.entry_exts().collect();
+    assert_eq!(read[0], playlist[0]);
+    assert_eq!(read[1], playlist[1]);
+    assert_eq!(read[2].entry, m3u::path_entry("./Beatles: Help.mp3"));
+}